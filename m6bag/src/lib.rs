@@ -1,12 +1,81 @@
 use std::{
     borrow::Borrow,
-    collections::{btree_map::Entry, BTreeMap},
+    cmp::Reverse,
+    collections::{btree_map, hash_map, BTreeMap, HashMap, VecDeque},
     fmt,
+    hash::{BuildHasher, Hash, RandomState},
+    iter::{repeat_n, Flatten},
+    ops::{Bound, RangeBounds},
 };
 
+#[cfg(feature = "serde")]
+mod serde_impl;
 
+#[cfg(feature = "serde")]
+pub use serde_impl::counted;
+
+
+/// Common interface of [`BTreeBag`] and [`HashBag`]
+pub trait Bag<T> {
+    /// return same item number after insert
+    fn insert(&mut self, item: T) -> usize;
+
+    fn count(&self, item: &T) -> usize;
+
+    fn get<'a>(&'a self, item: &T) -> Option<impl Iterator<Item = &'a T>>
+    where
+        T: 'a;
+
+    fn iter<'a>(&'a self) -> impl Iterator<Item = (&'a T, usize)>
+    where
+        T: 'a;
+
+    fn flat_iter<'a>(&'a self) -> impl Iterator<Item = &'a T>
+    where
+        T: 'a;
+
+    /// total item number (with multiplicity)
+    fn len(&self) -> usize {
+        self.iter().map(|(_, n)| n).sum()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+}
+
+
+/// Equality is multiset equality: same keys with same counts,
+/// the insertion order inside a bucket isn't compared
+#[derive(Clone)]
 pub struct BTreeBag<T> {
-    map: BTreeMap<T, Vec<T>>,
+    /// deque so that both `pop_first` and `pop_last` are O(1) in a bucket
+    map: BTreeMap<T, VecDeque<T>>,
+}
+
+pub struct HashBag<T, S = RandomState> {
+    map: HashMap<T, Vec<T>, S>,
+}
+
+/// Bag grouped by key extracted from item
+pub struct BTreeGroupBag<K, T, F = fn(&T) -> K> {
+    map: BTreeMap<K, Vec<T>>,
+    key_fn: F,
+}
+
+/// Counting-only bag, keep one item for each key instead of all duplicates
+pub struct BTreeCounter<T> {
+    map: BTreeMap<T, usize>,
+}
+
+/// Owning iterator of [`BTreeBag`], same order with flat_iter
+pub struct IntoIter<T> {
+    inner: Flatten<btree_map::IntoValues<T, VecDeque<T>>>,
+}
+
+/// Borrowing iterator of [`BTreeBag`], same order with flat_iter
+pub struct Iter<'a, T> {
+    inner: Flatten<btree_map::Values<'a, T, VecDeque<T>>>,
 }
 
 
@@ -25,13 +94,13 @@ impl<T: Ord> BTreeBag<T> {
         T: Clone,
     {
         match self.map.entry(item.clone()) {
-            Entry::Vacant(entry) => {
-                entry.insert(vec![item]);
+            btree_map::Entry::Vacant(entry) => {
+                entry.insert(VecDeque::from([item]));
                 1
             }
-            Entry::Occupied(mut entry) => {
+            btree_map::Entry::Occupied(mut entry) => {
                 let colls = entry.get_mut();
-                colls.push(item);
+                colls.push_back(item);
                 colls.len()
             }
         }
@@ -41,7 +110,7 @@ impl<T: Ord> BTreeBag<T> {
     where
         T: Borrow<Q>,
     {
-        if let Some(coll) = self.map.get(&key) {
+        if let Some(coll) = self.map.get(key) {
             coll.len()
         }
         else {
@@ -73,6 +142,135 @@ impl<T: Ord> BTreeBag<T> {
             None
         }
     }
+
+    /// (item, count) in key order for keys in `range`
+    pub fn range<Q: Ord, R: RangeBounds<Q>>(
+        &self,
+        range: R,
+    ) -> impl DoubleEndedIterator<Item = (&T, usize)>
+    where
+        T: Borrow<Q>,
+    {
+        self.map.range(range).map(|(k, v)| (k, v.len()))
+    }
+
+    /// item number (with multiplicity) for keys in `range`
+    pub fn count_range<Q: Ord, R: RangeBounds<Q>>(&self, range: R) -> usize
+    where
+        T: Borrow<Q>,
+    {
+        self.map.range(range).map(|(_, v)| v.len()).sum()
+    }
+
+    /// cursor at the first key `>= key`
+    pub fn lower_bound<Q: Ord>(
+        &self,
+        key: &Q,
+    ) -> impl DoubleEndedIterator<Item = (&T, usize)>
+    where
+        T: Borrow<Q>,
+    {
+        self.map
+            .range::<Q, _>((Bound::Included(key), Bound::Unbounded))
+            .map(|(k, v)| (k, v.len()))
+    }
+
+    /// cursor at the first key `> key`
+    pub fn upper_bound<Q: Ord>(
+        &self,
+        key: &Q,
+    ) -> impl DoubleEndedIterator<Item = (&T, usize)>
+    where
+        T: Borrow<Q>,
+    {
+        self.map
+            .range::<Q, _>((Bound::Excluded(key), Bound::Unbounded))
+            .map(|(k, v)| (k, v.len()))
+    }
+
+    pub fn first(&self) -> Option<(&T, usize)> {
+        self.map.first_key_value().map(|(k, v)| (k, v.len()))
+    }
+
+    pub fn last(&self) -> Option<(&T, usize)> {
+        self.map.last_key_value().map(|(k, v)| (k, v.len()))
+    }
+
+    /// remove the earliest inserted item of the smallest key
+    pub fn pop_first(&mut self) -> Option<T> {
+        let mut entry = self.map.first_entry()?;
+        let item = entry.get_mut().pop_front().unwrap();
+
+        if entry.get().is_empty() {
+            entry.remove();
+        }
+
+        Some(item)
+    }
+
+    /// remove the latest inserted item of the largest key
+    pub fn pop_last(&mut self) -> Option<T> {
+        let mut entry = self.map.last_entry()?;
+        let item = entry.get_mut().pop_back().unwrap();
+
+        if entry.get().is_empty() {
+            entry.remove();
+        }
+
+        Some(item)
+    }
+}
+
+/// Items of a bucket are all equal to its key (`Eq` is consistent with
+/// `Ord`), so comparing key and count is enough
+impl<T: PartialEq> PartialEq for BTreeBag<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.map.len() == other.map.len()
+            && self
+                .map
+                .iter()
+                .zip(other.map.iter())
+                .all(|((k1, v1), (k2, v2))| k1 == k2 && v1.len() == v2.len())
+    }
+}
+
+impl<T: Eq> Eq for BTreeBag<T> {}
+
+impl<T> Default for BTreeBag<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord + Clone> Bag<T> for BTreeBag<T> {
+    fn insert(&mut self, item: T) -> usize {
+        BTreeBag::insert(self, item)
+    }
+
+    fn count(&self, item: &T) -> usize {
+        BTreeBag::count(self, item)
+    }
+
+    fn get<'a>(&'a self, item: &T) -> Option<impl Iterator<Item = &'a T>>
+    where
+        T: 'a,
+    {
+        BTreeBag::get(self, item)
+    }
+
+    fn iter<'a>(&'a self) -> impl Iterator<Item = (&'a T, usize)>
+    where
+        T: 'a,
+    {
+        BTreeBag::iter(self)
+    }
+
+    fn flat_iter<'a>(&'a self) -> impl Iterator<Item = &'a T>
+    where
+        T: 'a,
+    {
+        BTreeBag::flat_iter(self)
+    }
 }
 
 impl<T: fmt::Debug> fmt::Debug for BTreeBag<T> {
@@ -85,6 +283,205 @@ impl<T: Ord + Clone> FromIterator<T> for BTreeBag<T> {
     fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
         let mut bag = Self::new();
 
+        bag.extend(iter);
+        bag
+    }
+}
+
+impl<T: Ord + Clone> Extend<T> for BTreeBag<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for i in iter {
+            self.insert(i);
+        }
+    }
+}
+
+/// Same order with flat_iter
+impl<T> IntoIterator for BTreeBag<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            inner: self.map.into_values().flatten(),
+        }
+    }
+}
+
+/// Same with flat_iter
+impl<'a, T> IntoIterator for &'a BTreeBag<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Iter {
+            inner: self.map.values().flatten(),
+        }
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+
+impl<T> HashBag<T> {
+    pub fn new() -> Self {
+        Self {
+            map: HashMap::new(),
+        }
+    }
+}
+
+impl<T, S> HashBag<T, S> {
+    pub fn with_hasher(hash_builder: S) -> Self {
+        Self {
+            map: HashMap::with_hasher(hash_builder),
+        }
+    }
+}
+
+impl<T: Hash + Eq, S: BuildHasher> HashBag<T, S> {
+    /// return same item number after insert
+    pub fn insert(&mut self, item: T) -> usize
+    where
+        T: Clone,
+    {
+        match self.map.entry(item.clone()) {
+            hash_map::Entry::Vacant(entry) => {
+                entry.insert(vec![item]);
+                1
+            }
+            hash_map::Entry::Occupied(mut entry) => {
+                let colls = entry.get_mut();
+                colls.push(item);
+                colls.len()
+            }
+        }
+    }
+
+    pub fn count<Q: Hash + Eq>(&self, key: &Q) -> usize
+    where
+        T: Borrow<Q>,
+    {
+        if let Some(coll) = self.map.get(key) {
+            coll.len()
+        }
+        else {
+            0
+        }
+    }
+
+    /// (item, count) in arbitrary order
+    pub fn iter(&self) -> impl Iterator<Item = (&T, usize)> {
+        self.map.iter().map(|(k, v)| (k, v.len()))
+    }
+
+    pub fn flat_iter(&self) -> impl Iterator<Item = &T> {
+        self.map.values().flatten()
+    }
+
+    pub fn get<Q: Hash + Eq>(
+        &self,
+        key: &Q,
+    ) -> Option<impl Iterator<Item = &T>>
+    where
+        T: Borrow<Q>,
+    {
+        if let Some(coll) = self.map.get(key) {
+            if coll.is_empty() {
+                None
+            }
+            else {
+                Some(coll.iter())
+            }
+        }
+        else {
+            None
+        }
+    }
+}
+
+impl<T, S: Default> Default for HashBag<T, S> {
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<T: Hash + Eq + Clone, S: BuildHasher> Bag<T> for HashBag<T, S> {
+    fn insert(&mut self, item: T) -> usize {
+        HashBag::insert(self, item)
+    }
+
+    fn count(&self, item: &T) -> usize {
+        HashBag::count(self, item)
+    }
+
+    fn get<'a>(&'a self, item: &T) -> Option<impl Iterator<Item = &'a T>>
+    where
+        T: 'a,
+    {
+        HashBag::get(self, item)
+    }
+
+    fn iter<'a>(&'a self) -> impl Iterator<Item = (&'a T, usize)>
+    where
+        T: 'a,
+    {
+        HashBag::iter(self)
+    }
+
+    fn flat_iter<'a>(&'a self) -> impl Iterator<Item = &'a T>
+    where
+        T: 'a,
+    {
+        HashBag::flat_iter(self)
+    }
+}
+
+impl<T: fmt::Debug, S> fmt::Debug for HashBag<T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.map, f)
+    }
+}
+
+impl<T: Hash + Eq + Clone, S: BuildHasher + Default> FromIterator<T>
+    for HashBag<T, S>
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut bag = Self::default();
+
         for i in iter {
             bag.insert(i);
         }
@@ -93,6 +490,257 @@ impl<T: Ord + Clone> FromIterator<T> for BTreeBag<T> {
     }
 }
 
+impl<T> BTreeCounter<T> {
+    pub fn new() -> Self {
+        Self {
+            map: BTreeMap::new(),
+        }
+    }
+}
+
+impl<T: Ord> BTreeCounter<T> {
+    /// return same item number after insert
+    pub fn add(&mut self, item: T) -> usize {
+        self.add_n(item, 1)
+    }
+
+    /// return same item number after insert
+    pub fn add_n(&mut self, item: T, n: usize) -> usize {
+        if n == 0 {
+            return self.count(&item);
+        }
+
+        let cnt = self.map.entry(item).or_default();
+        *cnt += n;
+        *cnt
+    }
+
+    /// remove at most `n` items, return same item number after remove
+    pub fn remove_n<Q: Ord>(&mut self, key: &Q, n: usize) -> usize
+    where
+        T: Borrow<Q>,
+    {
+        let Some(cnt) = self.map.get_mut(key)
+        else {
+            return 0;
+        };
+
+        if *cnt <= n {
+            self.map.remove(key);
+            0
+        }
+        else {
+            *cnt -= n;
+            *cnt
+        }
+    }
+
+    pub fn count<Q: Ord>(&self, key: &Q) -> usize
+    where
+        T: Borrow<Q>,
+    {
+        self.map.get(key).cloned().unwrap_or_default()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&T, usize)> {
+        self.map.iter().map(|(k, n)| (k, *n))
+    }
+
+    /// total item number (with multiplicity)
+    pub fn len(&self) -> usize {
+        self.map.values().sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// `k` most common items in count descending order,
+    /// ties are in key order
+    pub fn most_common(&self, k: usize) -> Vec<(&T, usize)> {
+        let mut ents = self.iter().collect::<Vec<_>>();
+
+        ents.sort_by_key(|(_, n)| Reverse(*n));
+        ents.truncate(k);
+        ents
+    }
+}
+
+impl<T> Default for BTreeCounter<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> Bag<T> for BTreeCounter<T> {
+    fn insert(&mut self, item: T) -> usize {
+        self.add(item)
+    }
+
+    fn count(&self, item: &T) -> usize {
+        BTreeCounter::count(self, item)
+    }
+
+    fn get<'a>(&'a self, item: &T) -> Option<impl Iterator<Item = &'a T>>
+    where
+        T: 'a,
+    {
+        self.map.get_key_value(item).map(|(k, n)| repeat_n(k, *n))
+    }
+
+    fn iter<'a>(&'a self) -> impl Iterator<Item = (&'a T, usize)>
+    where
+        T: 'a,
+    {
+        BTreeCounter::iter(self)
+    }
+
+    fn flat_iter<'a>(&'a self) -> impl Iterator<Item = &'a T>
+    where
+        T: 'a,
+    {
+        self.map.iter().flat_map(|(k, n)| repeat_n(k, *n))
+    }
+
+    fn len(&self) -> usize {
+        BTreeCounter::len(self)
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for BTreeCounter<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.map, f)
+    }
+}
+
+impl<T: Ord> FromIterator<T> for BTreeCounter<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut counter = Self::new();
+
+        for i in iter {
+            counter.add(i);
+        }
+
+        counter
+    }
+}
+
+/// drop the duplicates, keep the bucket key
+impl<T: Ord> From<BTreeBag<T>> for BTreeCounter<T> {
+    fn from(bag: BTreeBag<T>) -> Self {
+        Self {
+            map: bag.map.into_iter().map(|(k, v)| (k, v.len())).collect(),
+        }
+    }
+}
+
+/// duplicates are cloned from the key
+impl<T: Ord + Clone> From<BTreeCounter<T>> for BTreeBag<T> {
+    fn from(counter: BTreeCounter<T>) -> Self {
+        Self {
+            map: counter
+                .map
+                .into_iter()
+                .map(|(k, n)| {
+                    let v = repeat_n(k.clone(), n).collect();
+                    (k, v)
+                })
+                .collect(),
+        }
+    }
+}
+
+impl<K, T, F> BTreeGroupBag<K, T, F> {
+    pub fn new(key_fn: F) -> Self {
+        Self {
+            map: BTreeMap::new(),
+            key_fn,
+        }
+    }
+}
+
+impl<K: Ord, T, F: Fn(&T) -> K> BTreeGroupBag<K, T, F> {
+    /// return same group item number after insert
+    pub fn insert(&mut self, item: T) -> usize {
+        let colls = self.map.entry((self.key_fn)(&item)).or_default();
+
+        colls.push(item);
+        colls.len()
+    }
+
+    pub fn count<Q: Ord>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+    {
+        self.map.get(key).map(|coll| coll.len()).unwrap_or_default()
+    }
+
+    /// items of the group in insertion order
+    pub fn get<Q: Ord>(&self, key: &Q) -> Option<&[T]>
+    where
+        K: Borrow<Q>,
+    {
+        self.map.get(key).map(|coll| coll.as_slice())
+    }
+
+    pub fn remove_group<Q: Ord>(&mut self, key: &Q) -> Option<Vec<T>>
+    where
+        K: Borrow<Q>,
+    {
+        self.map.remove(key)
+    }
+
+    /// (key, group) in key order
+    pub fn groups(&self) -> impl Iterator<Item = (&K, &[T])> {
+        self.map.iter().map(|(k, v)| (k, v.as_slice()))
+    }
+
+    /// (key, group item number) in key order
+    pub fn iter(&self) -> impl Iterator<Item = (&K, usize)> {
+        self.map.iter().map(|(k, v)| (k, v.len()))
+    }
+
+    pub fn flat_iter(&self) -> impl Iterator<Item = &T> {
+        self.map.values().flatten()
+    }
+
+    /// (key, `stat(group)`) in key order
+    pub fn group_stats<'a, S>(
+        &'a self,
+        stat: impl Fn(&'a [T]) -> S + 'a,
+    ) -> impl Iterator<Item = (&'a K, S)> {
+        self.map.iter().map(move |(k, v)| (k, stat(v)))
+    }
+
+    /// group number
+    pub fn group_len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// total item number
+    pub fn len(&self) -> usize {
+        self.map.values().map(|v| v.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+}
+
+impl<K: Ord, T, F: Fn(&T) -> K> Extend<T> for BTreeGroupBag<K, T, F> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for i in iter {
+            self.insert(i);
+        }
+    }
+}
+
+impl<K: fmt::Debug, T: fmt::Debug, F> fmt::Debug for BTreeGroupBag<K, T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.map, f)
+    }
+}
+
 
 #[macro_export]
 macro_rules! btree_bag {
@@ -109,10 +757,25 @@ macro_rules! btree_bag {
     };
 }
 
+#[macro_export]
+macro_rules! hash_bag {
+    ( $($value:expr),* $(,)?) => {
+        {
+            let mut _bag = $crate::HashBag::new();
+
+            $(
+                _bag.insert($value);
+            )*
+
+            _bag
+        }
+    };
+}
+
 
 #[cfg(test)]
 mod tests {
-    use crate::BTreeBag;
+    use crate::{BTreeBag, BTreeCounter, BTreeGroupBag, Bag, HashBag};
 
     #[test]
     fn test_case1() {
@@ -180,6 +843,261 @@ mod tests {
 
         assert_eq!(ents, colls);
 
+        // same multiset in another insertion order
+        let bag2 = BTreeBag::from_iter(colls.iter().rev().cloned());
+
+        assert_eq!(bag, bag2);
+        assert_ne!(
+            bag.get(&A::new(3, 1, 0)).unwrap().next().unwrap().a3,
+            bag2.get(&A::new(3, 1, 0)).unwrap().next().unwrap().a3
+        );
+
         println!("{bag:#?}");
     }
+
+    #[test]
+    fn test_ordered() {
+        let mut bag = btree_bag! { 3, 2, 3, 2, 1, 3, 4, 2, 3, 6 };
+
+        assert_eq!(bag.first(), Some((&1, 1)));
+        assert_eq!(bag.last(), Some((&6, 1)));
+
+        assert_eq!(
+            bag.range(2..4).collect::<Vec<_>>(),
+            vec![(&2, 3), (&3, 4)]
+        );
+        assert_eq!(
+            bag.range(2..=4).rev().collect::<Vec<_>>(),
+            vec![(&4, 1), (&3, 4), (&2, 3)]
+        );
+        assert_eq!(bag.count_range(2..4), 7);
+        assert_eq!(bag.count_range(..), 10);
+        assert_eq!(bag.count_range(7..), 0);
+
+        assert_eq!(bag.lower_bound(&3).next(), Some((&3, 4)));
+        assert_eq!(bag.lower_bound(&5).next(), Some((&6, 1)));
+        assert_eq!(bag.upper_bound(&3).next(), Some((&4, 1)));
+        assert_eq!(bag.upper_bound(&6).next(), None);
+
+        assert_eq!(bag.pop_first(), Some(1));
+        assert_eq!(bag.first(), Some((&2, 3)));
+        assert_eq!(bag.pop_last(), Some(6));
+        assert_eq!(bag.pop_last(), Some(4));
+        assert_eq!(bag.pop_last(), Some(3));
+        assert_eq!(bag.last(), Some((&3, 3)));
+
+        let mut bag = BTreeBag::<usize>::new();
+
+        assert_eq!(bag.first(), None);
+        assert_eq!(bag.pop_first(), None);
+        assert_eq!(bag.pop_last(), None);
+    }
+
+    #[test]
+    fn test_hash_bag() {
+        let bag = hash_bag! { "c", "b", "c", "b", "a", "c", "d", "b", "c" };
+
+        assert_eq!(bag.count(&"c"), 4);
+        assert_eq!(bag.count(&"b"), 3);
+        assert_eq!(bag.count(&"a"), 1);
+        assert_eq!(bag.count(&"z"), 0);
+        assert!(bag.get(&"z").is_none());
+        assert_eq!(bag.get(&"b").unwrap().count(), 3);
+
+        let mut ents = bag.iter().collect::<Vec<_>>();
+        ents.sort();
+
+        assert_eq!(ents, vec![(&"a", 1), (&"b", 3), (&"c", 4), (&"d", 1)]);
+        assert_eq!(bag.flat_iter().count(), 9);
+
+        println!("{bag:?}");
+    }
+
+    #[test]
+    fn test_bag_trait() {
+        fn fill<B: Bag<usize> + Default>() -> B {
+            let mut bag = B::default();
+
+            for i in [3, 2, 3, 2, 1, 3] {
+                bag.insert(i);
+            }
+
+            bag
+        }
+
+        fn check<B: Bag<usize>>(bag: &B) {
+            assert_eq!(bag.len(), 6);
+            assert!(!bag.is_empty());
+            assert_eq!(bag.count(&3), 3);
+            assert_eq!(bag.count(&4), 0);
+            assert_eq!(bag.get(&2).unwrap().collect::<Vec<_>>(), [&2, &2]);
+            assert_eq!(bag.flat_iter().sum::<usize>(), 14);
+        }
+
+        check(&fill::<BTreeBag<usize>>());
+        check(&fill::<HashBag<usize>>());
+
+        assert!(HashBag::<usize>::new().is_empty());
+        assert_eq!(Bag::len(&BTreeBag::<usize>::new()), 0);
+    }
+
+    #[test]
+    fn test_counter() {
+        let mut counter = BTreeCounter::from_iter([3, 2, 3, 2, 1, 3, 4, 2, 3]);
+
+        assert_eq!(counter.count(&3), 4);
+        assert_eq!(counter.count(&10), 0);
+        assert_eq!(counter.len(), 9);
+
+        assert_eq!(counter.add_n(10, 5), 5);
+        assert_eq!(counter.add_n(10, 0), 5);
+        assert_eq!(counter.add_n(11, 0), 0);
+        assert_eq!(counter.count(&11), 0);
+        assert_eq!(counter.add(1), 2);
+
+        assert_eq!(counter.most_common(3), vec![(&10, 5), (&3, 4), (&2, 3)]);
+        assert_eq!(counter.most_common(10).len(), 5);
+
+        assert_eq!(counter.remove_n(&10, 2), 3);
+        assert_eq!(counter.remove_n(&10, 4), 0);
+        assert_eq!(counter.remove_n(&10, 1), 0);
+        assert_eq!(counter.most_common(2), vec![(&3, 4), (&2, 3)]);
+        assert_eq!(counter.most_common(0), vec![]);
+
+        assert_eq!(
+            counter.iter().collect::<Vec<_>>(),
+            vec![(&1, 2), (&2, 3), (&3, 4), (&4, 1)]
+        );
+        assert_eq!(
+            Bag::flat_iter(&counter).cloned().collect::<Vec<_>>(),
+            vec![1, 1, 2, 2, 2, 3, 3, 3, 3, 4]
+        );
+
+        let bag = BTreeBag::from(counter);
+
+        assert_eq!(bag.count(&3), 4);
+        assert_eq!(bag.get(&1).unwrap().collect::<Vec<_>>(), [&1, &1]);
+
+        let counter = BTreeCounter::from(bag);
+
+        assert_eq!(counter.len(), 10);
+        assert_eq!(counter.count(&2), 3);
+
+        println!("{counter:?}");
+    }
+
+    #[test]
+    fn test_group_bag() {
+        #[derive(Debug, PartialEq)]
+        struct A {
+            a1: usize,
+            a2: usize,
+            a3: usize,
+        }
+
+        let a = |a1, a2, a3| A { a1, a2, a3 };
+
+        let mut bag = BTreeGroupBag::new(|x: &A| (x.a1, x.a2));
+
+        assert_eq!(bag.insert(a(4, 1, 1)), 1);
+        assert_eq!(bag.insert(a(4, 1, 3)), 2);
+
+        bag.extend([
+            a(3, 1, 2),
+            a(3, 1, 3),
+            a(4, 2, 1),
+            a(3, 1, 1),
+            a(4, 2, 2),
+        ]);
+
+        assert_eq!(bag.len(), 7);
+        assert_eq!(bag.group_len(), 3);
+        assert_eq!(bag.count(&(3, 1)), 3);
+        assert_eq!(bag.count(&(5, 1)), 0);
+        assert!(bag.get(&(5, 1)).is_none());
+        assert_eq!(bag.get(&(4, 1)).unwrap(), [a(4, 1, 1), a(4, 1, 3)]);
+
+        assert_eq!(
+            bag.iter().collect::<Vec<_>>(),
+            vec![(&(3, 1), 3), (&(4, 1), 2), (&(4, 2), 2)]
+        );
+        assert_eq!(
+            bag.group_stats(|g| g.iter().map(|x| x.a3).max().unwrap())
+                .collect::<Vec<_>>(),
+            vec![(&(3, 1), 3), (&(4, 1), 3), (&(4, 2), 2)]
+        );
+        assert_eq!(
+            bag.flat_iter().map(|x| x.a3).collect::<Vec<_>>(),
+            vec![2, 3, 1, 1, 3, 1, 2]
+        );
+        assert_eq!(
+            bag.groups().map(|(k, g)| (*k, g.len())).collect::<Vec<_>>(),
+            vec![((3, 1), 3), ((4, 1), 2), ((4, 2), 2)]
+        );
+
+        assert_eq!(bag.remove_group(&(4, 1)).unwrap().len(), 2);
+        assert!(bag.remove_group(&(4, 1)).is_none());
+        assert_eq!(bag.len(), 5);
+
+        println!("{bag:?}");
+    }
+
+    #[test]
+    fn test_btree_bag_traits() {
+        let mut bag = btree_bag! { 3, 2, 3 };
+
+        bag.extend([1, 3]);
+
+        let bag2 = bag.clone();
+
+        assert_eq!(bag, bag2);
+        assert_ne!(bag, btree_bag! { 3, 2, 3, 1 });
+        assert_eq!(BTreeBag::<usize>::default(), BTreeBag::new());
+
+        assert_eq!(
+            (&bag).into_iter().collect::<Vec<_>>(),
+            [&1, &2, &3, &3, &3]
+        );
+
+        let mut total = 0;
+
+        for i in &bag {
+            total += i;
+        }
+
+        assert_eq!(total, 12);
+        assert_eq!((&bag).into_iter().next_back(), Some(&3));
+        assert_eq!(bag.into_iter().rev().collect::<Vec<_>>(), [3, 3, 3, 2, 1]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        use serde::{Deserialize, Serialize};
+
+        let bag = btree_bag! { 3, 2, 3, 1 };
+        let s = serde_json::to_string(&bag).unwrap();
+
+        assert_eq!(s, "[1,2,3,3]");
+        assert_eq!(serde_json::from_str::<BTreeBag<usize>>(&s).unwrap(), bag);
+
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct Snapshot {
+            #[serde(with = "crate::counted")]
+            bag: BTreeBag<String>,
+        }
+
+        let snapshot = Snapshot {
+            bag: btree_bag! { "b".to_owned(), "a".to_owned(), "b".to_owned() },
+        };
+        let s = serde_json::to_string(&snapshot).unwrap();
+
+        assert_eq!(s, r#"{"bag":[["a",1],["b",2]]}"#);
+        assert_eq!(serde_json::from_str::<Snapshot>(&s).unwrap(), snapshot);
+
+        let s = r#"{"bag":[["a",18446744073709551615]]}"#;
+        let err = serde_json::from_str::<Snapshot>(s).unwrap_err();
+
+        assert!(err.to_string().contains("count"), "{err}");
+    }
 }
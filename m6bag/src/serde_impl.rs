@@ -0,0 +1,74 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::BTreeBag;
+
+
+/// Serialize as flattened items (same order with flat_iter)
+impl<T: Serialize> Serialize for BTreeBag<T> {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self)
+    }
+}
+
+impl<'de, T: Deserialize<'de> + Ord + Clone> Deserialize<'de> for BTreeBag<T> {
+    fn deserialize<D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        Ok(Vec::<T>::deserialize(deserializer)?.into_iter().collect())
+    }
+}
+
+
+/// (De)serialize as sequence of `(item, count)`,
+/// use with `#[serde(with = "m6bag::counted")]`
+///
+/// # Note
+///
+/// only bucket key is kept, duplicates are cloned from it on deserialize,
+/// so a count over [`MAX_COUNT`](counted::MAX_COUNT) is rejected
+pub mod counted {
+    use serde::{
+        de::{Error, Unexpected},
+        Deserialize, Deserializer, Serialize, Serializer,
+    };
+
+    use crate::BTreeBag;
+
+    /// Max count of one item accepted on deserialize
+    pub const MAX_COUNT: usize = 1 << 20;
+
+    pub fn serialize<T: Serialize, S: Serializer>(
+        bag: &BTreeBag<T>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(bag.map.iter().map(|(k, v)| (k, v.len())))
+    }
+
+    pub fn deserialize<'de, T, D>(
+        deserializer: D,
+    ) -> Result<BTreeBag<T>, D::Error>
+    where
+        T: Deserialize<'de> + Ord + Clone,
+        D: Deserializer<'de>,
+    {
+        let mut bag = BTreeBag::new();
+
+        for (item, n) in Vec::<(T, usize)>::deserialize(deserializer)? {
+            if n > MAX_COUNT {
+                return Err(D::Error::invalid_value(
+                    Unexpected::Unsigned(n as u64),
+                    &"an item count no more than MAX_COUNT",
+                ));
+            }
+
+            for _ in 0..n {
+                bag.insert(item.clone());
+            }
+        }
+
+        Ok(bag)
+    }
+}
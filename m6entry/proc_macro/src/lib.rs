@@ -1,24 +1,9 @@
-#![allow(unused_imports)]
-#![allow(unused_import_braces)]
-
 extern crate proc_macro;
 
-use std::convert::identity;
-
 use proc_macro::TokenStream;
-use proc_macro2::Span;
 use quote::{format_ident, quote};
 use syn::parse::{Parse, ParseStream, Result};
-use syn::token::{Paren, Priv, Token, Brace};
-use syn::{
-    parse_macro_input, Expr, Ident, Lit, LitInt, LitStr, MacroDelimiter, Path,
-    Token,
-};
-
-use proc_macro_utils::{*, ident};
-
-////////////////////////////////////////////////////////////////////////////////
-//// Define New Custom Error
+use syn::{parse_macro_input, Index, LitInt};
 
 struct DefTOEnt {
     lit_meta_nums: LitInt,
@@ -34,40 +19,63 @@ impl Parse for DefTOEnt {
 }
 
 
+/// `deftoent!(N)` define tuple-ordered entry `TOEntryN<T1, ..., TN>`
 #[proc_macro]
 pub fn deftoent(input: TokenStream) -> TokenStream {
     let DefTOEnt { lit_meta_nums } = parse_macro_input!(input as DefTOEnt);
 
-    let meta_nums: usize = lit_meta_nums.base10_parse().unwrap();
-
-    let mut qs = quote! {};
-
-    let struct_name = ident!("TOEntry{meta_nums}");
-
-    /* Define Structure */
-
-    let _lbrace = lbrace();
-    let _rbrace = rbrace();
-    let sign_less = sign_less();
-    let sign_gt = sign_gt();
-
-
-    qs.extend(quote! {
-        pub struct #struct_name #sign_less
-    });
-
-    for i in 1..=meta_nums {
-        let ti = ident!("T{i}");
-
-        qs.extend(quote! {
-            #ti,
-        })
-    }
-
-    qs.extend(quote! {
-         #sign_gt
-    });
-
-
-    TokenStream::from(qs)
+    let meta_nums: usize = match lit_meta_nums.base10_parse() {
+        Ok(meta_nums) if meta_nums > 0 => meta_nums,
+        Ok(_) => {
+            return syn::Error::new(
+                lit_meta_nums.span(),
+                "entry should have at least one field",
+            )
+            .to_compile_error()
+            .into()
+        }
+        Err(err) => return err.to_compile_error().into(),
+    };
+
+    let struct_name = format_ident!("TOEntry{}", meta_nums);
+
+    let tys = (1..=meta_nums)
+        .map(|i| format_ident!("T{}", i))
+        .collect::<Vec<_>>();
+    let vars = (1..=meta_nums)
+        .map(|i| format_ident!("v{}", i))
+        .collect::<Vec<_>>();
+    let idxs = (0..meta_nums).map(Index::from).collect::<Vec<_>>();
+
+    let doc = format!("Tuple-ordered entry with {} fields", meta_nums);
+
+    TokenStream::from(quote! {
+        /* Define Structure */
+
+        #[doc = #doc]
+        #[derive(
+            Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default
+        )]
+        pub struct #struct_name<#(#tys),*>(#(pub #tys),*);
+
+        /* Implementation */
+
+        impl<#(#tys),*> #struct_name<#(#tys),*> {
+            pub fn drain(self) -> (#(#tys,)*) {
+                (#(self.#idxs,)*)
+            }
+        }
+
+        impl<#(#tys),*> From<(#(#tys,)*)> for #struct_name<#(#tys),*> {
+            fn from((#(#vars,)*): (#(#tys,)*)) -> Self {
+                Self(#(#vars),*)
+            }
+        }
+
+        impl<#(#tys),*> From<#struct_name<#(#tys),*>> for (#(#tys,)*) {
+            fn from(ent: #struct_name<#(#tys),*>) -> Self {
+                ent.drain()
+            }
+        }
+    })
 }
@@ -0,0 +1,336 @@
+use crate::KVEntry;
+
+
+////////////////////////////////////////////////////////////////////////////////
+//// Structure
+
+/// Which end of the key order pops first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HeapMode {
+    Min,
+    Max,
+}
+
+/// Handle of an entry pushed into `IndexedHeap`,
+/// stale after the entry is popped or removed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HeapHandle {
+    slot: usize,
+    gen: usize,
+}
+
+/// Binary heap of `KVEntry` with decrease/increase-key and removal by handle
+#[derive(Debug, Clone)]
+pub struct IndexedHeap<K, V> {
+    mode: HeapMode,
+    /// (entry, slot)
+    heap: Vec<(KVEntry<K, V>, usize)>,
+    slots: Vec<Slot>,
+    free_slots: Vec<usize>,
+}
+
+#[derive(Debug, Clone)]
+struct Slot {
+    /// position in heap
+    pos: Option<usize>,
+    gen: usize,
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+//// Implementation
+
+impl<K: Ord, V> IndexedHeap<K, V> {
+    pub fn new(mode: HeapMode) -> Self {
+        Self {
+            mode,
+            heap: vec![],
+            slots: vec![],
+            free_slots: vec![],
+        }
+    }
+
+    pub fn min() -> Self {
+        Self::new(HeapMode::Min)
+    }
+
+    pub fn max() -> Self {
+        Self::new(HeapMode::Max)
+    }
+
+    pub fn mode(&self) -> HeapMode {
+        self.mode
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    pub fn push(&mut self, key: K, value: V) -> HeapHandle {
+        let pos = self.heap.len();
+
+        let slot = if let Some(slot) = self.free_slots.pop() {
+            self.slots[slot].pos = Some(pos);
+            slot
+        }
+        else {
+            self.slots.push(Slot {
+                pos: Some(pos),
+                gen: 0,
+            });
+            self.slots.len() - 1
+        };
+
+        self.heap.push((KVEntry(key, value), slot));
+        self.sift_up(pos);
+
+        HeapHandle {
+            slot,
+            gen: self.slots[slot].gen,
+        }
+    }
+
+    pub fn peek(&self) -> Option<&KVEntry<K, V>> {
+        self.heap.first().map(|(ent, _)| ent)
+    }
+
+    pub fn pop(&mut self) -> Option<KVEntry<K, V>> {
+        if self.heap.is_empty() {
+            return None;
+        }
+
+        Some(self.remove_at(0))
+    }
+
+    pub fn contains(&self, handle: HeapHandle) -> bool {
+        self.pos(handle).is_some()
+    }
+
+    pub fn get(&self, handle: HeapHandle) -> Option<&KVEntry<K, V>> {
+        self.pos(handle).map(|pos| &self.heap[pos].0)
+    }
+
+    /// Value can be changed freely, only key affects the order
+    pub fn get_value_mut(&mut self, handle: HeapHandle) -> Option<&mut V> {
+        self.pos(handle).map(|pos| &mut self.heap[pos].0 .1)
+    }
+
+    /// Replace key of the entry, return the old key,
+    /// None if the handle is stale
+    pub fn change_priority(
+        &mut self,
+        handle: HeapHandle,
+        new_key: K,
+    ) -> Option<K> {
+        let pos = self.pos(handle)?;
+        let old_key = std::mem::replace(&mut self.heap[pos].0 .0, new_key);
+
+        self.sift_up(pos);
+        self.sift_down(self.slots[handle.slot].pos.unwrap());
+
+        Some(old_key)
+    }
+
+    /// None if the handle is stale
+    pub fn remove(&mut self, handle: HeapHandle) -> Option<KVEntry<K, V>> {
+        let pos = self.pos(handle)?;
+
+        Some(self.remove_at(pos))
+    }
+
+    /// Arbitrary order
+    pub fn iter(&self) -> impl Iterator<Item = &KVEntry<K, V>> {
+        self.heap.iter().map(|(ent, _)| ent)
+    }
+
+    fn pos(&self, handle: HeapHandle) -> Option<usize> {
+        let slot = self.slots.get(handle.slot)?;
+
+        if slot.gen == handle.gen {
+            slot.pos
+        }
+        else {
+            None
+        }
+    }
+
+    fn remove_at(&mut self, pos: usize) -> KVEntry<K, V> {
+        let last = self.heap.len() - 1;
+
+        self.swap(pos, last);
+
+        let (ent, slot) = self.heap.pop().unwrap();
+
+        self.slots[slot].pos = None;
+        self.slots[slot].gen += 1;
+        self.free_slots.push(slot);
+
+        if pos < self.heap.len() {
+            self.sift_up(pos);
+            self.sift_down(pos);
+        }
+
+        ent
+    }
+
+    /// Whether entry at `i` should be closer to the top than entry at `j`
+    fn before(&self, i: usize, j: usize) -> bool {
+        let (a, b) = (&self.heap[i].0 .0, &self.heap[j].0 .0);
+
+        match self.mode {
+            HeapMode::Min => a < b,
+            HeapMode::Max => a > b,
+        }
+    }
+
+    fn swap(&mut self, i: usize, j: usize) {
+        self.heap.swap(i, j);
+        self.slots[self.heap[i].1].pos = Some(i);
+        self.slots[self.heap[j].1].pos = Some(j);
+    }
+
+    fn sift_up(&mut self, mut pos: usize) {
+        while pos > 0 {
+            let parent = (pos - 1) / 2;
+
+            if !self.before(pos, parent) {
+                break;
+            }
+
+            self.swap(pos, parent);
+            pos = parent;
+        }
+    }
+
+    fn sift_down(&mut self, mut pos: usize) {
+        let len = self.heap.len();
+
+        loop {
+            let mut top = pos;
+
+            for child in [2 * pos + 1, 2 * pos + 2] {
+                if child < len && self.before(child, top) {
+                    top = child;
+                }
+            }
+
+            if top == pos {
+                break;
+            }
+
+            self.swap(pos, top);
+            pos = top;
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_indexed_heap() {
+        let mut heap = IndexedHeap::min();
+
+        let h3 = heap.push(3, 'c');
+        let h1 = heap.push(1, 'a');
+        let h5 = heap.push(5, 'e');
+        let h4 = heap.push(4, 'd');
+
+        assert_eq!(heap.len(), 4);
+        assert_eq!(heap.peek().map(|ent| ent.1), Some('a'));
+
+        assert_eq!(heap.change_priority(h5, 0), Some(5));
+        assert_eq!(heap.peek().map(|ent| ent.1), Some('e'));
+
+        assert_eq!(heap.change_priority(h5, 10), Some(0));
+        assert_eq!(heap.get(h5).map(|ent| ent.0), Some(10));
+        assert_eq!(heap.remove(h3).map(|ent| ent.drain()), Some((3, 'c')));
+        assert_eq!(heap.remove(h3), None);
+        assert!(!heap.contains(h3));
+
+        *heap.get_value_mut(h4).unwrap() = 'D';
+
+        assert_eq!(heap.pop().map(|ent| ent.drain()), Some((1, 'a')));
+        assert_eq!(heap.change_priority(h1, 0), None);
+
+        // reused slot doesn't revive the stale handle
+        let h2 = heap.push(2, 'b');
+
+        assert!(heap.get(h1).is_none() && heap.get(h3).is_none());
+        assert_eq!(heap.get(h2).map(|ent| ent.1), Some('b'));
+
+        let order = std::iter::from_fn(|| heap.pop())
+            .map(|ent| ent.drain())
+            .collect::<Vec<_>>();
+
+        assert_eq!(order, [(2, 'b'), (4, 'D'), (10, 'e')]);
+        assert!(heap.is_empty());
+        assert_eq!(heap.pop(), None);
+
+        let mut heap = IndexedHeap::max();
+
+        let handles =
+            (0..100).map(|i| heap.push(i % 17, i)).collect::<Vec<_>>();
+
+        for h in handles.iter().step_by(3) {
+            heap.remove(*h);
+        }
+
+        for h in handles.iter().skip(1).step_by(5) {
+            if let Some(k) = heap.get(*h).map(|ent| ent.0) {
+                heap.change_priority(*h, (k * 7) % 19);
+            }
+        }
+
+        let keys = std::iter::from_fn(|| heap.pop())
+            .map(|ent| ent.0)
+            .collect::<Vec<_>>();
+
+        assert_eq!(keys.len(), 66);
+        assert!(keys.windows(2).all(|w| w[0] >= w[1]));
+    }
+
+    #[test]
+    fn test_dijkstra() {
+        let edges: &[&[(usize, usize)]] = &[
+            &[(1, 7), (2, 9), (5, 14)],
+            &[(0, 7), (2, 10), (3, 15)],
+            &[(0, 9), (1, 10), (3, 11), (5, 2)],
+            &[(1, 15), (2, 11), (4, 6)],
+            &[(3, 6), (5, 9)],
+            &[(0, 14), (2, 2), (4, 9)],
+        ];
+
+        let mut dist = vec![usize::MAX; edges.len()];
+        let mut handles = vec![None; edges.len()];
+        let mut heap = IndexedHeap::min();
+
+        dist[0] = 0;
+        handles[0] = Some(heap.push(0, 0));
+
+        while let Some(KVEntry(d, u)) = heap.pop() {
+            for &(v, w) in edges[u] {
+                if d + w >= dist[v] {
+                    continue;
+                }
+
+                dist[v] = d + w;
+
+                match handles[v] {
+                    Some(h) if heap.contains(h) => {
+                        heap.change_priority(h, dist[v]);
+                    }
+                    _ => handles[v] = Some(heap.push(dist[v], v)),
+                }
+            }
+        }
+
+        assert_eq!(dist, [0, 7, 9, 20, 20, 11]);
+    }
+}
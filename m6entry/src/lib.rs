@@ -1,21 +1,67 @@
+#![allow(clippy::four_forward_slashes)]
+
+use std::borrow::Borrow;
 use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+
+pub use m6entry_macros::deftoent;
+
+mod indexed_heap;
+mod sorted_vec_map;
+
+pub use indexed_heap::*;
+pub use sorted_vec_map::*;
 
 
 ////////////////////////////////////////////////////////////////////////////////
 //// Macro
 
+deftoent!(2);
+deftoent!(3);
+deftoent!(4);
+deftoent!(5);
+deftoent!(6);
+deftoent!(7);
+deftoent!(8);
+deftoent!(9);
+deftoent!(10);
+deftoent!(11);
+deftoent!(12);
 
 
 ////////////////////////////////////////////////////////////////////////////////
 //// Structure
 
-/// KeyValue Pair
+/// KeyValue Pair, compared and hashed by key only
 #[derive(Debug, Clone, Copy)]
 pub struct KVEntry<K, V>(pub K, pub V);
 
+/// Totally ordered `f32` (by `f32::total_cmp`), usable as `Ord` key
+///
+/// `-NaN < -inf < ... < -0.0 < +0.0 < ... < +inf < +NaN`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TotalF32(pub f32);
+
+/// Totally ordered `f64` (by `f64::total_cmp`), usable as `Ord` key
+///
+/// `-NaN < -inf < ... < -0.0 < +0.0 < ... < +inf < +NaN`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TotalF64(pub f64);
+
+/// Entry ordered (and hashed) by key `K` only, value `V` is ignored.
+///
+/// The key is usually a tuple of `Asc` / `Desc` fields,
+/// e.g. `OrdBy<(Desc<A>, Asc<B>), Payload>`
+#[derive(Debug, Clone, Copy)]
+pub struct OrdBy<K, V>(pub K, pub V);
+
+/// Ascending key field
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Asc<T>(pub T);
 
-#[derive(PartialEq, Eq, PartialOrd, Ord)]
-pub struct TOEntry2<T1, T2>(pub T1, pub T2);
+/// Descending key field
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Desc<T>(pub T);
 
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -25,6 +71,14 @@ impl<K, V> KVEntry<K, V> {
     pub fn drain(self) -> (K, V) {
         (self.0, self.1)
     }
+
+    pub fn map_key<K2, F: FnOnce(K) -> K2>(self, f: F) -> KVEntry<K2, V> {
+        KVEntry(f(self.0), self.1)
+    }
+
+    pub fn map_value<V2, F: FnOnce(V) -> V2>(self, f: F) -> KVEntry<K, V2> {
+        KVEntry(self.0, f(self.1))
+    }
 }
 
 impl<K: PartialEq, V> PartialEq for KVEntry<K, V> {
@@ -44,16 +98,143 @@ impl<K: Eq, V> Eq for KVEntry<K, V> {
 
 impl<K: Ord, V> Ord for KVEntry<K, V> {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.partial_cmp(&other).unwrap()
+        self.0.cmp(&other.0)
+    }
+}
+
+impl<K: Hash, V> Hash for KVEntry<K, V> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
+/// Query maps / sets of entries by key
+impl<K, V> Borrow<K> for KVEntry<K, V> {
+    fn borrow(&self) -> &K {
+        &self.0
+    }
+}
+
+impl<K, V> From<(K, V)> for KVEntry<K, V> {
+    fn from((k, v): (K, V)) -> Self {
+        Self(k, v)
+    }
+}
+
+impl<K, V> From<KVEntry<K, V>> for (K, V) {
+    fn from(ent: KVEntry<K, V>) -> Self {
+        ent.drain()
     }
 }
 
-impl<K, V> Into<(K, V)> for KVEntry<K, V> {
-    fn into(self) -> (K, V) {
+impl<K, V> OrdBy<K, V> {
+    pub fn drain(self) -> (K, V) {
         (self.0, self.1)
     }
 }
 
+impl<K: PartialEq, V> PartialEq for OrdBy<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<K: Eq, V> Eq for OrdBy<K, V> {}
+
+impl<K: PartialOrd, V> PartialOrd for OrdBy<K, V> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.0.partial_cmp(&other.0)
+    }
+}
+
+impl<K: Ord, V> Ord for OrdBy<K, V> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.cmp(&other.0)
+    }
+}
+
+impl<K: Hash, V> Hash for OrdBy<K, V> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
+impl<K, V> From<(K, V)> for OrdBy<K, V> {
+    fn from((k, v): (K, V)) -> Self {
+        Self(k, v)
+    }
+}
+
+impl<K, V> From<OrdBy<K, V>> for (K, V) {
+    fn from(ent: OrdBy<K, V>) -> Self {
+        ent.drain()
+    }
+}
+
+impl<T: PartialOrd> PartialOrd for Desc<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        other.0.partial_cmp(&self.0)
+    }
+}
+
+impl<T: Ord> Ord for Desc<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.0.cmp(&self.0)
+    }
+}
+
+
+macro_rules! impl_total_float {
+    ($($name:ident: $fty:ty),+) => {
+        $(
+            impl PartialEq for $name {
+                fn eq(&self, other: &Self) -> bool {
+                    self.cmp(other).is_eq()
+                }
+            }
+
+            impl Eq for $name {}
+
+            impl PartialOrd for $name {
+                fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                    Some(self.cmp(other))
+                }
+            }
+
+            impl Ord for $name {
+                fn cmp(&self, other: &Self) -> Ordering {
+                    self.0.total_cmp(&other.0)
+                }
+            }
+
+            /// Consistent with `Eq`: equal iff same bits
+            impl Hash for $name {
+                fn hash<H: Hasher>(&self, state: &mut H) {
+                    self.0.to_bits().hash(state)
+                }
+            }
+
+            impl From<$fty> for $name {
+                fn from(value: $fty) -> Self {
+                    Self(value)
+                }
+            }
+
+            impl From<$name> for $fty {
+                fn from(value: $name) -> Self {
+                    value.0
+                }
+            }
+        )+
+    };
+}
+
+
+impl_total_float! {
+    TotalF32: f32,
+    TotalF64: f64
+}
+
 
 
 #[cfg(test)]
@@ -83,6 +264,112 @@ mod test {
     }
 
 
+    #[test]
+    fn test_kventry_key() {
+        use std::collections::{BTreeSet, HashMap, HashSet};
+
+        let set = [KVEntry("a", 1), KVEntry("b", 2), KVEntry("a", 3)]
+            .into_iter()
+            .collect::<HashSet<_>>();
+
+        assert_eq!(set.len(), 2);
+        assert!(set.contains(&"b"));
+        assert!(!set.contains(&"c"));
+        assert_eq!(set.get(&"a").map(|ent| ent.0), Some("a"));
+
+        let set = (0..10)
+            .map(|i| KVEntry(i, i * i))
+            .collect::<BTreeSet<_>>();
+
+        assert_eq!(set.get(&3).map(|ent| ent.1), Some(9));
+        assert_eq!(
+            set.range(7..).map(|ent| ent.1).collect::<Vec<_>>(),
+            [49, 64, 81]
+        );
+
+        let ent = KVEntry(2, "v")
+            .map_key(|k| k * 10)
+            .map_value(str::len);
+
+        assert_eq!(ent.drain(), (20, 1));
+        assert_eq!(KVEntry::from((1, 'a')).1, 'a');
+
+        let mut counts = HashMap::new();
+        *counts.entry(KVEntry(1, ())).or_insert(0) += 1;
+        *counts.entry(KVEntry(1, ())).or_insert(0) += 1;
+
+        assert_eq!(counts.get(&1), Some(&2));
+    }
+
+    #[test]
+    fn test_total_float() {
+        use std::collections::{BinaryHeap, HashSet};
+        use std::cmp::Reverse;
+
+        let mut heap = BinaryHeap::new();
+
+        heap.push(Reverse(KVEntry(TotalF64(2.5), 'a')));
+        heap.push(Reverse(KVEntry(TotalF64(f64::NAN), 'b')));
+        heap.push(Reverse(KVEntry(TotalF64(-1.0), 'c')));
+        heap.push(Reverse(KVEntry(TotalF64(f64::INFINITY), 'd')));
+
+        let order = std::iter::from_fn(|| heap.pop())
+            .map(|Reverse(ent)| ent.1)
+            .collect::<Vec<_>>();
+
+        assert_eq!(order, ['c', 'a', 'd', 'b']);
+
+        assert_eq!(TotalF32(f32::NAN), TotalF32(f32::NAN));
+        assert!(TotalF32(-0.0) < TotalF32(0.0));
+        assert!(TotalF32(-f32::NAN) < TotalF32(f32::NEG_INFINITY));
+        assert_eq!(f64::from(TotalF64::from(1.5)), 1.5);
+
+        let set = [TotalF64(f64::NAN), TotalF64(f64::NAN), TotalF64(0.0)]
+            .into_iter()
+            .collect::<HashSet<_>>();
+
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn test_ordby() {
+        use std::collections::{BinaryHeap, HashSet};
+
+        #[derive(Debug, PartialEq)]
+        struct Task(&'static str);
+
+        // max-heap: lower cost first, then larger priority
+        let mut heap = BinaryHeap::new();
+
+        heap.push(OrdBy((Desc(3), Asc(1)), Task("a")));
+        heap.push(OrdBy((Desc(1), Asc(1)), Task("b")));
+        heap.push(OrdBy((Desc(1), Asc(5)), Task("c")));
+        heap.push(OrdBy((Desc(2), Asc(0)), Task("d")));
+
+        let order = std::iter::from_fn(|| heap.pop())
+            .map(|ent| ent.1 .0)
+            .collect::<Vec<_>>();
+
+        assert_eq!(order, ["c", "b", "d", "a"]);
+
+        assert!(Desc(1.0) > Desc(2.0));
+        assert!(Asc(1.0) < Asc(2.0));
+        assert_eq!(Desc(1.0).partial_cmp(&Desc(f64::NAN)), None);
+
+        assert_eq!(OrdBy(Asc(1), "x"), OrdBy(Asc(1), "y"));
+        assert!(OrdBy(Desc(1), "x") > OrdBy(Desc(2), "a"));
+
+        let set = [OrdBy(1, 'a'), OrdBy(1, 'b'), OrdBy(2, 'a')]
+            .into_iter()
+            .collect::<HashSet<_>>();
+
+        assert_eq!(set.len(), 2);
+
+        let ent: OrdBy<_, _> = (Desc(1), "v").into();
+
+        assert_eq!(<(_, _)>::from(ent), (Desc(1), "v"));
+    }
+
     #[test]
     fn test_toentry() {
         assert!((3, 2) > (3, 1));
@@ -93,6 +380,40 @@ mod test {
 
         assert!((2, 3) < (3, 2));
         assert!((2, 2) < (3, 3));
+
+        assert!(TOEntry2(3, 2) > TOEntry2(3, 1));
+        assert!(TOEntry2(3, 2) < TOEntry2(3, 3));
+        assert!(TOEntry2(3, 3) == TOEntry2(3, 3));
+        assert!(TOEntry2(2, 3) < TOEntry2(3, 2));
+
+        assert!(TOEntry3(1, "b", 0.5) < TOEntry3(1, "b", 1.0));
+        assert!(TOEntry3(1, "c", 0.5) > TOEntry3(1, "b", 1.0));
+
+        let ent = TOEntry12::from((1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12));
+
+        assert_eq!(ent.11, 12);
+        assert_eq!(<(_, _, _, _, _, _, _, _, _, _, _, _)>::from(ent).0, 1);
+
+        let ent: TOEntry4<u8, char, &str, bool> = (1, 'a', "b", true).into();
+
+        assert_eq!(ent.drain(), (1, 'a', "b", true));
+        assert_eq!(TOEntry2::<usize, usize>::default(), TOEntry2(0, 0));
+
+        let set = [TOEntry2(1, 'a'), TOEntry2(1, 'a'), TOEntry2(1, 'b')]
+            .into_iter()
+            .collect::<std::collections::HashSet<_>>();
+
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn test_deftoent() {
+        deftoent!(13);
+
+        let ent = TOEntry13(1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13);
+
+        assert!(ent < TOEntry13(1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 14));
+        assert_eq!(ent.drain().12, 13);
     }
 
 
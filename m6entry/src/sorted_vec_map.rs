@@ -0,0 +1,377 @@
+use std::{
+    borrow::Borrow,
+    fmt,
+    hash::{Hash, Hasher},
+    ops::{Bound, RangeBounds},
+    slice, vec,
+};
+
+use crate::KVEntry;
+
+
+////////////////////////////////////////////////////////////////////////////////
+//// Structure
+
+/// Which one to keep when bulk-building from entries with the same key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DupKeyPolicy {
+    KeepFirst,
+    KeepLast,
+}
+
+/// Flat map, `KVEntry` sorted by key and stored contiguously
+///
+/// O(log n) lookup, O(n) insert / remove, suitable for read-mostly map
+#[derive(Clone)]
+pub struct SortedVecMap<K, V> {
+    ents: Vec<KVEntry<K, V>>,
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+//// Implementation
+
+impl<K, V> SortedVecMap<K, V> {
+    pub fn new() -> Self {
+        Self { ents: vec![] }
+    }
+
+    pub fn with_capacity(cap: usize) -> Self {
+        Self {
+            ents: Vec::with_capacity(cap),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.ents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ents.is_empty()
+    }
+
+    /// Entries in key order
+    pub fn as_slice(&self) -> &[KVEntry<K, V>] {
+        &self.ents
+    }
+
+    pub fn iter(&self) -> slice::Iter<'_, KVEntry<K, V>> {
+        self.ents.iter()
+    }
+
+    pub fn keys(&self) -> impl DoubleEndedIterator<Item = &K> {
+        self.ents.iter().map(|ent| &ent.0)
+    }
+
+    pub fn values(&self) -> impl DoubleEndedIterator<Item = &V> {
+        self.ents.iter().map(|ent| &ent.1)
+    }
+
+    pub fn first(&self) -> Option<&KVEntry<K, V>> {
+        self.ents.first()
+    }
+
+    pub fn last(&self) -> Option<&KVEntry<K, V>> {
+        self.ents.last()
+    }
+
+    pub fn clear(&mut self) {
+        self.ents.clear()
+    }
+}
+
+impl<K: Ord, V> SortedVecMap<K, V> {
+    /// Build from unsorted entries, O(n log n)
+    pub fn from_unsorted<I: IntoIterator<Item = (K, V)>>(
+        iter: I,
+        policy: DupKeyPolicy,
+    ) -> Self {
+        let mut ents = iter
+            .into_iter()
+            .map(|(k, v)| KVEntry(k, v))
+            .collect::<Vec<_>>();
+
+        // make the kept one be the first of the same keys after stable sort
+        if policy == DupKeyPolicy::KeepLast {
+            ents.reverse();
+        }
+
+        ents.sort_by(|a, b| a.0.cmp(&b.0));
+        ents.dedup_by(|later, former| later.0 == former.0);
+
+        Self { ents }
+    }
+
+    pub fn get<Q: Ord + ?Sized>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+    {
+        self.get_key_value(key).map(|(_, v)| v)
+    }
+
+    pub fn get_key_value<Q: Ord + ?Sized>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+    {
+        self.search(key)
+            .ok()
+            .map(|idx| (&self.ents[idx].0, &self.ents[idx].1))
+    }
+
+    pub fn get_mut<Q: Ord + ?Sized>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+    {
+        self.search(key).ok().map(|idx| &mut self.ents[idx].1)
+    }
+
+    pub fn contains_key<Q: Ord + ?Sized>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        self.search(key).is_ok()
+    }
+
+    /// Return the old value if the key exists (the key isn't updated)
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.search(&key) {
+            Ok(idx) => Some(std::mem::replace(&mut self.ents[idx].1, value)),
+            Err(idx) => {
+                self.ents.insert(idx, KVEntry(key, value));
+                None
+            }
+        }
+    }
+
+    pub fn remove<Q: Ord + ?Sized>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+    {
+        self.remove_entry(key).map(|(_, v)| v)
+    }
+
+    pub fn remove_entry<Q: Ord + ?Sized>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+    {
+        self.search(key)
+            .ok()
+            .map(|idx| self.ents.remove(idx).drain())
+    }
+
+    /// Entries with keys in `range`
+    pub fn range<Q: Ord + ?Sized, R: RangeBounds<Q>>(
+        &self,
+        range: R,
+    ) -> &[KVEntry<K, V>]
+    where
+        K: Borrow<Q>,
+    {
+        let start = match range.start_bound() {
+            Bound::Included(q) => self.partition_point(|k| k < q),
+            Bound::Excluded(q) => self.partition_point(|k| k <= q),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(q) => self.partition_point(|k| k <= q),
+            Bound::Excluded(q) => self.partition_point(|k| k < q),
+            Bound::Unbounded => self.len(),
+        };
+
+        &self.ents[start..end.max(start)]
+    }
+
+    fn search<Q: Ord + ?Sized>(&self, key: &Q) -> Result<usize, usize>
+    where
+        K: Borrow<Q>,
+    {
+        self.ents.binary_search_by(|ent| ent.0.borrow().cmp(key))
+    }
+
+    fn partition_point<Q: ?Sized, P: Fn(&Q) -> bool>(&self, pred: P) -> usize
+    where
+        K: Borrow<Q>,
+    {
+        self.ents.partition_point(|ent| pred(ent.0.borrow()))
+    }
+}
+
+/// Compare (key, value) pairs, `KVEntry` itself only compares keys
+impl<K: PartialEq, V: PartialEq> PartialEq for SortedVecMap<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .zip(other.iter())
+                .all(|(a, b)| a.0 == b.0 && a.1 == b.1)
+    }
+}
+
+impl<K: Eq, V: Eq> Eq for SortedVecMap<K, V> {}
+
+/// Consistent with `PartialEq`, hash (key, value) pairs
+impl<K: Hash, V: Hash> Hash for SortedVecMap<K, V> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.len());
+
+        for ent in self.iter() {
+            ent.0.hash(state);
+            ent.1.hash(state);
+        }
+    }
+}
+
+impl<K, V> Default for SortedVecMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The last one wins on duplicate keys, like `BTreeMap`
+impl<K: Ord, V> FromIterator<(K, V)> for SortedVecMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self::from_unsorted(iter, DupKeyPolicy::KeepLast)
+    }
+}
+
+impl<K, V> IntoIterator for SortedVecMap<K, V> {
+    type Item = KVEntry<K, V>;
+    type IntoIter = vec::IntoIter<KVEntry<K, V>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.ents.into_iter()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a SortedVecMap<K, V> {
+    type Item = &'a KVEntry<K, V>;
+    type IntoIter = slice::Iter<'a, KVEntry<K, V>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K, V> From<SortedVecMap<K, V>> for Vec<KVEntry<K, V>> {
+    fn from(map: SortedVecMap<K, V>) -> Self {
+        map.ents
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for SortedVecMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.ents.iter().map(|ent| (&ent.0, &ent.1)))
+            .finish()
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_sorted_vec_map() {
+        let mut map = SortedVecMap::new();
+
+        assert_eq!(map.insert("b".to_owned(), 2), None);
+        assert_eq!(map.insert("d".to_owned(), 4), None);
+        assert_eq!(map.insert("a".to_owned(), 1), None);
+        assert_eq!(map.insert("c".to_owned(), 0), None);
+        assert_eq!(map.insert("c".to_owned(), 3), Some(0));
+
+        assert_eq!(map.len(), 4);
+        assert_eq!(map.get("c"), Some(&3));
+        assert_eq!(map.get("e"), None);
+        assert!(map.contains_key("a"));
+
+        *map.get_mut("a").unwrap() += 10;
+
+        assert_eq!(map.keys().collect::<Vec<_>>(), ["a", "b", "c", "d"]);
+        assert_eq!(map.values().collect::<Vec<_>>(), [&11, &2, &3, &4]);
+
+        let values = |ents: &[KVEntry<String, i32>]| {
+            ents.iter().map(|ent| ent.1).collect::<Vec<_>>()
+        };
+
+        let range = |start, end| map.range::<str, _>((start, end));
+
+        assert_eq!(
+            values(range(Bound::Included("b"), Bound::Excluded("d"))),
+            [2, 3]
+        );
+        assert_eq!(
+            values(range(Bound::Excluded("a"), Bound::Included("d"))),
+            [2, 3, 4]
+        );
+        assert_eq!(
+            values(range(Bound::Unbounded, Bound::Unbounded)),
+            [11, 2, 3, 4]
+        );
+        assert_eq!(
+            values(range(Bound::Included("bb"), Bound::Excluded("ba"))),
+            []
+        );
+
+        assert_eq!(map.remove("b"), Some(2));
+        assert_eq!(map.remove("b"), None);
+        assert_eq!(map.remove_entry("d"), Some(("d".to_owned(), 4)));
+        assert_eq!(format!("{map:?}"), r#"{"a": 11, "c": 3}"#);
+
+        let ents: Vec<_> = map.into();
+
+        assert_eq!(ents.len(), 2);
+    }
+
+    #[test]
+    fn test_sorted_vec_map_build() {
+        let ents = [(3, 'a'), (1, 'b'), (3, 'c'), (2, 'd'), (1, 'e')];
+
+        let map = SortedVecMap::from_unsorted(ents, DupKeyPolicy::KeepFirst);
+
+        assert_eq!(
+            map.iter().map(|ent| ent.drain()).collect::<Vec<_>>(),
+            [(1, 'b'), (2, 'd'), (3, 'a')]
+        );
+
+        let map = SortedVecMap::from_unsorted(ents, DupKeyPolicy::KeepLast);
+
+        assert_eq!(
+            map.into_iter().map(|ent| ent.drain()).collect::<Vec<_>>(),
+            [(1, 'e'), (2, 'd'), (3, 'c')]
+        );
+
+        let map = ents.into_iter().collect::<SortedVecMap<_, _>>();
+
+        assert_eq!(map.get(&3), Some(&'c'));
+        assert_eq!(map.first().map(|ent| ent.0), Some(1));
+        assert_eq!(map.last().map(|ent| ent.0), Some(3));
+        assert_eq!((&map).into_iter().count(), 3);
+        assert!(SortedVecMap::<usize, usize>::default().is_empty());
+
+        let values =
+            map.range(2..).iter().map(|ent| ent.1).collect::<String>();
+
+        assert_eq!(values, "dc");
+        assert_eq!(map.range(..=1).len(), 1);
+    }
+
+    #[test]
+    fn test_sorted_vec_map_eq() {
+        use std::hash::BuildHasher;
+
+        let map1 = SortedVecMap::from_iter([(1, 'a'), (2, 'b')]);
+        let map2 = SortedVecMap::from_iter([(2, 'b'), (1, 'a')]);
+        let map3 = SortedVecMap::from_iter([(1, 'b'), (2, 'b')]);
+
+        assert_eq!(map1, map2);
+        assert_ne!(map1, map3);
+        assert_ne!(map1, SortedVecMap::from_iter([(1, 'a')]));
+
+        let state = std::hash::RandomState::new();
+
+        assert_eq!(state.hash_one(&map1), state.hash_one(&map2));
+        assert_ne!(state.hash_one(&map1), state.hash_one(&map3));
+    }
+}
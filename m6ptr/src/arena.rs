@@ -0,0 +1,270 @@
+use std::{
+    cell::{Cell, RefCell},
+    fmt,
+    ptr::NonNull,
+};
+
+use crate::{Ptr, liveness::Token};
+
+
+////////////////////////////////////////////////////////////////////////////////
+//// Constants
+
+const INIT_CHUNK_CAP: usize = 16;
+
+
+////////////////////////////////////////////////////////////////////////////////
+//// Structures
+
+/// Typed arena, allocate in chunks and hand out `Ptr` handles
+///
+/// Like the `Ptr` of an `OwnedPtr`, the handles are valid until the arena
+/// is dropped or reset, which is checked with feature `liveness-check`:
+///
+/// ```
+/// let arena = m6ptr::Arena::new();
+/// let one = arena.alloc(1);
+/// let two = arena.alloc(*one + 1);
+///
+/// assert_eq!(*one + *two, 3);
+/// ```
+pub struct Arena<T> {
+    /// a chunk never grows over its capacity, so items never move
+    chunks: RefCell<Vec<Vec<T>>>,
+    len: Cell<usize>,
+    /// replaced on reset
+    token: Token,
+}
+
+/// In allocation order, including items allocated during the iteration
+pub struct Iter<'a, T> {
+    arena: &'a Arena<T>,
+    chunk: usize,
+    idx: usize,
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+//// Implementations
+
+impl<T> Arena<T> {
+    pub fn new() -> Self {
+        Self::with_capacity(INIT_CHUNK_CAP)
+    }
+
+    /// Capacity of the first chunk
+    pub fn with_capacity(cap: usize) -> Self {
+        Self {
+            chunks: RefCell::new(vec![Vec::with_capacity(cap.max(1))]),
+            len: Cell::new(0),
+            token: Token::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len.get()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Total capacity of all chunks
+    pub fn capacity(&self) -> usize {
+        self.chunks.borrow().iter().map(Vec::capacity).sum()
+    }
+
+    pub fn alloc(&self, value: T) -> Ptr<T> {
+        let mut chunks = self.chunks.borrow_mut();
+        let mut chunk = chunks.last_mut().unwrap();
+
+        if chunk.len() == chunk.capacity() {
+            let cap = chunk.capacity().saturating_mul(2);
+
+            chunks.push(Vec::with_capacity(cap));
+            chunk = chunks.last_mut().unwrap();
+        }
+
+        chunk.push(value);
+        self.len.set(self.len.get() + 1);
+
+        Ptr {
+            value: unsafe {
+                NonNull::new_unchecked(chunk.as_mut_ptr().add(chunk.len() - 1))
+            },
+            generation: self.token.generation(),
+        }
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            arena: self,
+            chunk: 0,
+            idx: 0,
+        }
+    }
+
+    /// Drop all items and keep the largest chunk for reuse,
+    /// all handles given out before are invalidated
+    pub fn reset(&mut self) {
+        let chunks = self.chunks.get_mut();
+        let mut last = chunks.pop().unwrap();
+
+        last.clear();
+
+        chunks.clear();
+        chunks.push(last);
+        self.len.set(0);
+        self.token = Token::new();
+    }
+}
+
+impl<T> Default for Arena<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Arena<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Arena<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let chunks = self.arena.chunks.borrow();
+
+        loop {
+            let chunk = chunks.get(self.chunk)?;
+
+            if self.idx < chunk.len() {
+                self.idx += 1;
+
+                return Some(unsafe { &*chunk.as_ptr().add(self.idx - 1) });
+            }
+
+            if self.chunk + 1 == chunks.len() {
+                return None;
+            }
+
+            self.chunk += 1;
+            self.idx = 0;
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::DropCounter;
+
+    #[derive(Debug)]
+    enum Expr {
+        Num(i32),
+        Add(Ptr<Expr>, Ptr<Expr>),
+    }
+
+    fn eval(expr: &Expr) -> i32 {
+        match expr {
+            Expr::Num(n) => *n,
+            Expr::Add(lf, rh) => eval(lf) + eval(rh),
+        }
+    }
+
+    #[test]
+    fn test_arena() {
+        let arena = Arena::with_capacity(2);
+
+        let mut sum = arena.alloc(Expr::Num(0));
+
+        for i in 1..=100 {
+            let n = arena.alloc(Expr::Num(i));
+
+            sum = arena.alloc(Expr::Add(sum, n));
+        }
+
+        assert_eq!(arena.len(), 201);
+        assert!(arena.capacity() >= 201);
+        assert_eq!(eval(&sum), 5050);
+        assert_eq!(
+            arena
+                .iter()
+                .filter(|expr| matches!(expr, Expr::Num(_)))
+                .count(),
+            101
+        );
+
+        // allocate during the iteration
+        let mut iter = arena.iter().skip(200);
+
+        assert_eq!(eval(iter.next().unwrap()), 5050);
+        arena.alloc(Expr::Num(7));
+        assert_eq!(eval(iter.next().unwrap()), 7);
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn test_arena_reset() {
+        let mut arena = Arena::with_capacity(2);
+
+        for i in 0..100 {
+            arena.alloc(i);
+        }
+
+        let cap = arena.capacity();
+
+        arena.reset();
+
+        assert!(arena.is_empty());
+        assert!(arena.capacity() > cap / 2);
+
+        let n = arena.alloc(1);
+
+        assert_eq!(*n, 1);
+        assert_eq!(format!("{arena:?}"), "[1]");
+    }
+
+    #[test]
+    fn test_arena_drop() {
+        let drops = DropCounter::new();
+        let mut arena = Arena::new();
+
+        for _ in 0..100 {
+            arena.alloc(drops.make());
+        }
+
+        arena.reset();
+        assert_eq!(drops.get(), 100);
+
+        for _ in 0..10 {
+            arena.alloc(drops.make());
+        }
+
+        drop(arena);
+        assert_eq!(drops.get(), 110);
+    }
+
+    #[test]
+    fn test_arena_zst() {
+        let arena = Arena::new();
+
+        for _ in 0..1000 {
+            arena.alloc(());
+        }
+
+        assert_eq!(arena.iter().count(), 1000);
+    }
+}
@@ -0,0 +1,588 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt,
+    hash::Hash,
+    marker::PhantomData,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use crate::{OwnedPtr, Ptr};
+
+
+////////////////////////////////////////////////////////////////////////////////
+//// Structures
+
+/// Opaque node handle of `PtrGraph`, can't be dereferenced,
+/// the node is reached through the graph
+pub struct NodePtr<N, E> {
+    /// unique among all graphs
+    id: u64,
+    _marker: PhantomData<fn() -> (N, E)>,
+}
+
+/// Node of `PtrGraph`, owned by the graph
+struct GraphNode<N, E> {
+    value: N,
+    id: u64,
+    out_edges: Vec<Edge<N, E>>,
+    /// source of each in edge (repeated for parallel edges)
+    in_edges: Vec<Link<N, E>>,
+}
+
+type Link<N, E> = Ptr<GraphNode<N, E>>;
+
+struct Edge<N, E> {
+    to: Link<N, E>,
+    value: E,
+}
+
+/// Directed graph, nodes are owned by the graph as `OwnedPtr` and edges are
+/// `Ptr`, so cycles are free to build and drop
+///
+/// Node handles are checked against the graph without being dereferenced,
+/// a handle from another graph or of a removed node is rejected.
+///
+/// Values are borrowed from the graph, not from the handle:
+///
+/// ```compile_fail
+/// let mut graph = m6ptr::PtrGraph::<i32>::new();
+/// let a = graph.add_node(1);
+/// let value = graph.node(a);
+///
+/// drop(graph);
+/// println!("{value}");
+/// ```
+pub struct PtrGraph<N, E = ()> {
+    nodes: Vec<OwnedPtr<GraphNode<N, E>>>,
+    /// node id => index in `nodes`
+    index: HashMap<u64, usize>,
+    edge_count: usize,
+}
+
+/// Preorder
+pub struct Dfs<'a, N, E> {
+    stack: Vec<Link<N, E>>,
+    visited: HashSet<u64>,
+    _graph: &'a PtrGraph<N, E>,
+}
+
+pub struct Bfs<'a, N, E> {
+    queue: VecDeque<Link<N, E>>,
+    visited: HashSet<u64>,
+    _graph: &'a PtrGraph<N, E>,
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+//// Implementations
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Read only, writes go through `PtrGraph::raw_mut`
+fn raw<N, E>(node: Link<N, E>) -> *const GraphNode<N, E> {
+    node.value.as_ptr()
+}
+
+impl<N, E> NodePtr<N, E> {
+    fn new(id: u64) -> Self {
+        Self {
+            id,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<N, E> Clone for NodePtr<N, E> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<N, E> Copy for NodePtr<N, E> {}
+
+impl<N, E> PartialEq for NodePtr<N, E> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl<N, E> Eq for NodePtr<N, E> {}
+
+impl<N, E> Hash for NodePtr<N, E> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.id.hash(state)
+    }
+}
+
+impl<N, E> fmt::Debug for NodePtr<N, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("NodePtr").field(&self.id).finish()
+    }
+}
+
+impl<N, E> PtrGraph<N, E> {
+    pub fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            index: HashMap::new(),
+            edge_count: 0,
+        }
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edge_count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Whether the (alive) node is of this graph
+    pub fn contains(&self, node: NodePtr<N, E>) -> bool {
+        self.index.contains_key(&node.id)
+    }
+
+    pub fn add_node(&mut self, value: N) -> NodePtr<N, E> {
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+
+        self.index.insert(id, self.nodes.len());
+        self.nodes.push(OwnedPtr::new(GraphNode {
+            value,
+            id,
+            out_edges: Vec::new(),
+            in_edges: Vec::new(),
+        }));
+
+        NodePtr::new(id)
+    }
+
+    /// Remove with all its edges, return None if it isn't in the graph
+    pub fn remove_node(&mut self, node: NodePtr<N, E>) -> Option<N> {
+        let idx = self.index.remove(&node.id)?;
+        let node = self.nodes[idx].ptr();
+
+        let GraphNode {
+            value,
+            out_edges,
+            in_edges,
+            ..
+        } = OwnedPtr::into_inner(self.nodes.swap_remove(idx));
+
+        if let Some(moved) = self.nodes.get(idx) {
+            self.index.insert(moved.id, idx);
+        }
+
+        let is_other = |other: &Link<N, E>| !Ptr::ptr_eq(other, &node);
+
+        for edge in out_edges.iter().filter(|edge| is_other(&edge.to)) {
+            let in_edges = unsafe { &mut (*self.raw_mut(edge.to)).in_edges };
+            let pos = in_edges.iter().position(|src| !is_other(src)).unwrap();
+
+            in_edges.swap_remove(pos);
+        }
+
+        let mut sources = in_edges.iter().filter(|src| is_other(src)).count();
+
+        for src in in_edges.iter().filter(|src| is_other(src)) {
+            let out_edges = unsafe { &mut (*self.raw_mut(*src)).out_edges };
+
+            // parallel edges are removed at the first visit
+            out_edges.retain(|edge| is_other(&edge.to));
+        }
+
+        sources += out_edges.len();
+        self.edge_count -= sources;
+
+        Some(value)
+    }
+
+    /// Parallel edges are allowed
+    ///
+    /// # Panics
+    ///
+    /// If `from` or `to` isn't in the graph
+    pub fn add_edge(
+        &mut self,
+        from: NodePtr<N, E>,
+        to: NodePtr<N, E>,
+        value: E,
+    ) {
+        let (from, to) = (self.link(from), self.link(to));
+
+        unsafe {
+            (*self.raw_mut(from)).out_edges.push(Edge { to, value });
+            (*self.raw_mut(to)).in_edges.push(from);
+        }
+
+        self.edge_count += 1;
+    }
+
+    /// Remove the earliest edge from `from` to `to`
+    pub fn remove_edge(
+        &mut self,
+        from: NodePtr<N, E>,
+        to: NodePtr<N, E>,
+    ) -> Option<E> {
+        let (Some(from), Some(to)) = (self.get(from), self.get(to))
+        else {
+            return None;
+        };
+
+        let out_edges = unsafe { &mut (*self.raw_mut(from)).out_edges };
+        let pos = out_edges
+            .iter()
+            .position(|edge| Ptr::ptr_eq(&edge.to, &to))?;
+        let edge = out_edges.remove(pos);
+
+        let in_edges = unsafe { &mut (*self.raw_mut(to)).in_edges };
+        let pos = in_edges
+            .iter()
+            .position(|src| Ptr::ptr_eq(src, &from))
+            .unwrap();
+
+        in_edges.swap_remove(pos);
+        self.edge_count -= 1;
+
+        Some(edge.value)
+    }
+
+    pub fn find_edge(
+        &self,
+        from: NodePtr<N, E>,
+        to: NodePtr<N, E>,
+    ) -> Option<&E> {
+        let to = self.get(to)?;
+
+        unsafe { &*raw(self.get(from)?) }
+            .out_edges
+            .iter()
+            .find(|edge| Ptr::ptr_eq(&edge.to, &to))
+            .map(|edge| &edge.value)
+    }
+
+    /// # Panics
+    ///
+    /// If `node` isn't in the graph
+    pub fn node(&self, node: NodePtr<N, E>) -> &N {
+        &self.graph_node(node).value
+    }
+
+    /// # Panics
+    ///
+    /// If `node` isn't in the graph
+    pub fn node_mut(&mut self, node: NodePtr<N, E>) -> &mut N {
+        unsafe { &mut (*self.raw_mut(self.link(node))).value }
+    }
+
+    /// In insertion order until a node is removed
+    pub fn nodes(&self) -> impl Iterator<Item = NodePtr<N, E>> + '_ {
+        self.nodes.iter().map(|node| NodePtr::new(node.id))
+    }
+
+    /// Out neighbors in insertion order of the edges
+    pub fn neighbors(
+        &self,
+        node: NodePtr<N, E>,
+    ) -> impl Iterator<Item = NodePtr<N, E>> + '_ {
+        self.graph_node(node)
+            .out_edges
+            .iter()
+            .map(|edge| NodePtr::new(edge.to.id))
+    }
+
+    pub fn edges(
+        &self,
+        node: NodePtr<N, E>,
+    ) -> impl Iterator<Item = (NodePtr<N, E>, &E)> + '_ {
+        self.graph_node(node)
+            .out_edges
+            .iter()
+            .map(|edge| (NodePtr::new(edge.to.id), &edge.value))
+    }
+
+    /// In neighbors (repeated for parallel edges)
+    pub fn predecessors(
+        &self,
+        node: NodePtr<N, E>,
+    ) -> impl Iterator<Item = NodePtr<N, E>> + '_ {
+        self.graph_node(node)
+            .in_edges
+            .iter()
+            .map(|src| NodePtr::new(src.id))
+    }
+
+    pub fn out_degree(&self, node: NodePtr<N, E>) -> usize {
+        self.graph_node(node).out_edges.len()
+    }
+
+    pub fn in_degree(&self, node: NodePtr<N, E>) -> usize {
+        self.graph_node(node).in_edges.len()
+    }
+
+    pub fn dfs(&self, start: NodePtr<N, E>) -> Dfs<'_, N, E> {
+        Dfs {
+            stack: vec![self.link(start)],
+            visited: HashSet::new(),
+            _graph: self,
+        }
+    }
+
+    pub fn bfs(&self, start: NodePtr<N, E>) -> Bfs<'_, N, E> {
+        Bfs {
+            queue: VecDeque::from([self.link(start)]),
+            visited: HashSet::from([start.id]),
+            _graph: self,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.index.clear();
+        self.edge_count = 0;
+    }
+
+    fn get(&self, node: NodePtr<N, E>) -> Option<Link<N, E>> {
+        self.index.get(&node.id).map(|&idx| self.nodes[idx].ptr())
+    }
+
+    fn link(&self, node: NodePtr<N, E>) -> Link<N, E> {
+        self.get(node).expect("node isn't in the graph")
+    }
+
+    fn graph_node(&self, node: NodePtr<N, E>) -> &GraphNode<N, E> {
+        unsafe { &*raw(self.link(node)) }
+    }
+
+    /// Write access to the (alive) node, taken from `&mut` of its owner
+    fn raw_mut(&mut self, node: Link<N, E>) -> *mut GraphNode<N, E> {
+        let idx = self.index[&node.id];
+
+        OwnedPtr::as_mut_ptr(&mut self.nodes[idx])
+    }
+}
+
+impl<N, E> Default for PtrGraph<N, E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N: fmt::Debug, E: fmt::Debug> fmt::Debug for PtrGraph<N, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut map = f.debug_map();
+
+        for node in self.nodes.iter() {
+            let node = unsafe { &*raw(node.ptr()) };
+            let edges = node
+                .out_edges
+                .iter()
+                .map(|edge| (unsafe { &(*raw(edge.to)).value }, &edge.value))
+                .collect::<Vec<_>>();
+
+            map.entry(&node.value, &edges);
+        }
+
+        map.finish()
+    }
+}
+
+impl<N, E> Iterator for Dfs<'_, N, E> {
+    type Item = NodePtr<N, E>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(node) = self.stack.pop() {
+            if !self.visited.insert(node.id) {
+                continue;
+            }
+
+            // reversed so that the first neighbor is visited first
+            let neighbors = node
+                .out_edges
+                .iter()
+                .map(|edge| edge.to)
+                .filter(|next| !self.visited.contains(&next.id))
+                .collect::<Vec<_>>();
+
+            self.stack.extend(neighbors.into_iter().rev());
+
+            return Some(NodePtr::new(node.id));
+        }
+
+        None
+    }
+}
+
+impl<N, E> Iterator for Bfs<'_, N, E> {
+    type Item = NodePtr<N, E>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.queue.pop_front()?;
+
+        for edge in node.out_edges.iter() {
+            if self.visited.insert(edge.to.id) {
+                self.queue.push_back(edge.to);
+            }
+        }
+
+        Some(NodePtr::new(node.id))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::DropCounter;
+
+    fn values<N: Clone, E>(
+        graph: &PtrGraph<N, E>,
+        iter: impl Iterator<Item = NodePtr<N, E>>,
+    ) -> Vec<N> {
+        iter.map(|node| graph.node(node).clone()).collect()
+    }
+
+    #[test]
+    fn test_ptr_graph() {
+        // entry -> cond -> body -> cond (loop), cond -> exit
+        let mut cfg = PtrGraph::new();
+
+        let entry = cfg.add_node("entry");
+        let cond = cfg.add_node("cond");
+        let body = cfg.add_node("body");
+        let exit = cfg.add_node("exit");
+
+        cfg.add_edge(entry, cond, ());
+        cfg.add_edge(cond, body, ());
+        cfg.add_edge(cond, exit, ());
+        cfg.add_edge(body, cond, ());
+        cfg.add_edge(body, body, ());
+
+        assert_eq!(cfg.node_count(), 4);
+        assert_eq!(cfg.edge_count(), 5);
+        assert_eq!(values(&cfg, cfg.neighbors(cond)), ["body", "exit"]);
+        assert_eq!(values(&cfg, cfg.predecessors(cond)), ["entry", "body"]);
+        assert_eq!(
+            values(&cfg, cfg.dfs(entry)),
+            ["entry", "cond", "body", "exit"]
+        );
+        assert_eq!(
+            values(&cfg, cfg.bfs(entry)),
+            ["entry", "cond", "body", "exit"]
+        );
+        assert_eq!(values(&cfg, cfg.dfs(exit)), ["exit"]);
+        assert!(cfg.find_edge(body, cond).is_some());
+        assert!(cfg.find_edge(cond, entry).is_none());
+
+        *cfg.node_mut(exit) = "ret";
+
+        assert_eq!(cfg.remove_node(body), Some("body"));
+        assert_eq!(cfg.edge_count(), 2);
+        assert_eq!(values(&cfg, cfg.neighbors(cond)), ["ret"]);
+        assert_eq!(cfg.in_degree(cond), 1);
+        assert_eq!(values(&cfg, cfg.nodes()), ["entry", "cond", "ret"]);
+
+        assert_eq!(cfg.remove_edge(entry, cond), Some(()));
+        assert_eq!(cfg.remove_edge(entry, cond), None);
+        assert_eq!(cfg.edge_count(), 1);
+        assert_eq!(values(&cfg, cfg.bfs(entry)), ["entry"]);
+    }
+
+    #[test]
+    fn test_ptr_graph_parallel_edges() {
+        let mut graph = PtrGraph::new();
+
+        let a = graph.add_node('a');
+        let b = graph.add_node('b');
+        let c = graph.add_node('c');
+
+        graph.add_edge(a, b, 1);
+        graph.add_edge(a, b, 2);
+        graph.add_edge(b, a, 3);
+        graph.add_edge(c, b, 4);
+
+        assert_eq!(graph.edge_count(), 4);
+        assert_eq!(graph.remove_edge(a, b), Some(1));
+        assert_eq!(graph.find_edge(a, b), Some(&2));
+        graph.add_edge(a, b, 5);
+
+        assert_eq!(graph.remove_node(a), Some('a'));
+        assert_eq!(graph.edge_count(), 1);
+        assert_eq!(graph.in_degree(b), 1);
+        assert_eq!(graph.out_degree(b), 0);
+        assert_eq!(format!("{graph:?}"), "{'c': [('b', 4)], 'b': []}");
+
+        let mut other = PtrGraph::<char, i32>::new();
+        let x = other.add_node('x');
+
+        assert!(!graph.contains(x));
+        assert_eq!(graph.remove_node(x), None);
+        assert_eq!(graph.remove_edge(x, b), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "node isn't in the graph")]
+    fn test_ptr_graph_foreign_node() {
+        let mut graph = PtrGraph::new();
+        let mut other = PtrGraph::new();
+
+        let a = graph.add_node(1);
+        let x = other.add_node(2);
+
+        graph.add_edge(a, x, ());
+    }
+
+    #[test]
+    fn test_ptr_graph_drop() {
+        let drops = DropCounter::new();
+
+        {
+            let mut graph = PtrGraph::new();
+
+            let nodes = (0..100)
+                .map(|_| graph.add_node(drops.make()))
+                .collect::<Vec<_>>();
+
+            // a complete graph with self loops
+            for from in nodes.iter() {
+                for to in nodes.iter() {
+                    graph.add_edge(*from, *to, ());
+                }
+            }
+
+            drop(graph.remove_node(nodes[0]));
+
+            assert_eq!(drops.get(), 1);
+            assert_eq!(graph.edge_count(), 99 * 99);
+            assert_eq!(graph.dfs(nodes[1]).count(), 99);
+        }
+
+        assert_eq!(drops.get(), 100);
+    }
+
+    #[test]
+    fn test_ptr_graph_removed_node() {
+        let mut graph = PtrGraph::<_>::new();
+        let a = graph.add_node(1);
+        let b = graph.add_node(2);
+
+        graph.add_edge(a, b, ());
+
+        assert_eq!(graph.remove_node(a), Some(1));
+        assert!(!graph.contains(a));
+        assert_eq!(graph.remove_node(a), None);
+        assert_eq!(graph.find_edge(a, b), None);
+        assert_eq!(graph.remove_edge(a, b), None);
+
+        // the index of `a` is reused
+        let c = graph.add_node(3);
+
+        assert_ne!(a, c);
+        assert!(!graph.contains(a));
+        assert_eq!(graph.node(c), &3);
+        assert_eq!(graph.in_degree(b), 0);
+    }
+}
@@ -1,15 +1,35 @@
-#![feature(unsafe_cell_access)]
+#![feature(box_as_ptr)]
+#![feature(mapped_lock_guards)]
+#![allow(clippy::four_forward_slashes)]
+
+pub mod arena;
+pub mod graph;
+pub mod list;
+mod liveness;
+mod rc;
+#[cfg(test)]
+mod test_util;
 
 use std::{
     borrow::{Borrow, BorrowMut},
-    cell::{OnceCell, UnsafeCell},
     fmt::Debug,
     hash::Hash,
+    marker::PhantomData,
+    mem::ManuallyDrop,
     ops::{Deref, DerefMut},
     ptr::NonNull,
-    sync::{LazyLock, LockResult, RwLock, RwLockReadGuard, RwLockWriteGuard},
+    sync::{
+        MappedRwLockReadGuard, MappedRwLockWriteGuard, OnceLock, PoisonError,
+        RwLock, RwLockReadGuard, RwLockWriteGuard,
+    },
 };
 
+pub use arena::Arena;
+pub use graph::{NodePtr, PtrGraph};
+pub use list::{CursorMut, NodeHandle, PtrList};
+use liveness::{Generation, Token};
+pub use rc::{ArcPtr, RcPtr, WeakArcPtr, WeakRcPtr};
+
 
 ////////////////////////////////////////////////////////////////////////////////
 //// Structures
@@ -25,91 +45,286 @@ use std::{
 ///
 /// derive by value T instead of pointer to T
 ///
-#[derive(PartialEq, Eq, PartialOrd, Ord, Hash)]
-#[repr(transparent)]
+/// With feature `liveness-check` (debug build), dereferencing a `Ptr` whose
+/// `OwnedPtr` has been dropped panics instead of being UB.
+///
+#[cfg_attr(
+    not(all(feature = "liveness-check", debug_assertions)),
+    repr(transparent)
+)]
 pub struct OwnedPtr<T: ?Sized> {
     value: Box<T>,
+    token: Token,
 }
 
-#[repr(transparent)]
+/// Shared (copyable) handle derived from `OwnedPtr`, read only.
+///
+/// Mutable access goes through `PtrMut` or the unsafe `Ptr::as_mut`.
+///
+/// It's `Send`/`Sync` as `&T` is, so it's rejected for a `!Sync` `T`:
+///
+/// ```compile_fail
+/// fn assert_send<T: Send>() {}
+///
+/// assert_send::<m6ptr::Ptr<std::cell::Cell<i32>>>();
+/// ```
+///
+/// and copies can't be written through:
+///
+/// ```compile_fail
+/// let owned = m6ptr::OwnedPtr::new(1);
+/// let mut ptr = owned.ptr();
+///
+/// *ptr = 2;
+/// ```
+#[cfg_attr(
+    not(all(feature = "liveness-check", debug_assertions)),
+    repr(transparent)
+)]
 pub struct Ptr<T: ?Sized> {
     value: NonNull<T>,
+    generation: Generation,
+}
+
+/// Unique mutable handle, can't be copied or cloned:
+///
+/// ```compile_fail
+/// let mut owned = m6ptr::OwnedPtr::new(1);
+/// let ptr_mut = owned.ptr_mut();
+/// let other: m6ptr::PtrMut<'_, i32> = Clone::clone(&ptr_mut);
+/// ```
+///
+/// and when it's borrowed from `OwnedPtr`, it can't outlive the owner:
+///
+/// ```compile_fail
+/// let mut owned = m6ptr::OwnedPtr::new(1);
+/// let mut ptr_mut = owned.ptr_mut();
+///
+/// drop(owned);
+/// *ptr_mut = 2;
+/// ```
+///
+/// It's `Send`/`Sync` as `&mut T` is:
+///
+/// ```compile_fail
+/// fn assert_send<T: Send>() {}
+///
+/// assert_send::<m6ptr::PtrMut<std::rc::Rc<i32>>>();
+/// ```
+#[cfg_attr(
+    not(all(feature = "liveness-check", debug_assertions)),
+    repr(transparent)
+)]
+pub struct PtrMut<'a, T: ?Sized> {
+    value: NonNull<T>,
+    generation: Generation,
+    _marker: PhantomData<&'a mut T>,
 }
 
 ////////////////////////////////////////
 //// Self-referential Structure Model
 
+/// Type family of the dependent borrowing from the owner of `SelfRef`
+///
+/// `shorten` should be implemented as identity (`dep`), which only compiles
+/// for a type covariant over `'a`, use `dependent!` for it.
+///
+/// ```compile_fail
+/// // invariant over 'a
+/// m6ptr::dependent!(Slot<'a> = std::cell::Cell<&'a str>);
+/// ```
+pub trait Dependent {
+    type Of<'a>;
+
+    fn shorten<'a, 'b: 'a>(dep: &'a Self::Of<'b>) -> &'a Self::Of<'a>;
+}
+
+/// Owner and the dependent borrowing from it in one value,
+/// e.g. a source `String` and its tokens `Vec<&str>`
+///
+/// The owner is boxed (pinned) so that moving `SelfRef` keeps the borrow
+/// valid, and it's never exposed mutably while the dependent is alive.
+///
+/// ```
+/// m6ptr::dependent!(Words<'a> = Vec<&'a str>);
+///
+/// let mut words = m6ptr::SelfRef::<String, Words>::new(
+///     "a b".to_owned(),
+///     |s| s.split(' ').collect(),
+/// );
+///
+/// assert_eq!(words.dependent(), &["a", "b"]);
+///
+/// words = words.rebuild(|s| s.push_str(" c"), |s| s.split(' ').collect());
+///
+/// assert_eq!(words.dependent(), &["a", "b", "c"]);
+/// ```
+///
+/// The dependent can't escape from the borrow:
+///
+/// ```compile_fail
+/// m6ptr::dependent!(Words<'a> = Vec<&'a str>);
+///
+/// let words = m6ptr::SelfRef::<String, Words>::new(
+///     "a b".to_owned(),
+///     |s| s.split(' ').collect(),
+/// );
+/// let first = words.dependent()[0];
+///
+/// drop(words);
+/// println!("{first}");
+/// ```
+pub struct SelfRef<O, D: Dependent> {
+    /// borrows from `owner`, declared (and dropped) first
+    dependent: ManuallyDrop<D::Of<'static>>,
+    owner: NonNull<O>,
+    _marker: PhantomData<Box<O>>,
+}
 
 ////////////////////////////////////////
 //// Static Variables
 
-/// Init (once) on main thread, shared bettwen threads
+/// Init (once), shared bettwen threads
 ///
 /// should be defined static variable not const
 #[derive(Debug)]
 #[repr(transparent)]
 pub struct OnceStatic<T> {
-    cell: OnceCell<T>,
+    cell: OnceLock<T>,
 }
 
-#[repr(transparent)]
+/// Init lazily by `F`, and can be replaced or reinit (e.g. swap global
+/// configuration in tests)
+///
+/// should be defined static variable not const
 pub struct LazyStatic<T, F = fn() -> T> {
-    value: UnsafeCell<RwLock<LazyLock<T, F>>>,
+    value: RwLock<Option<T>>,
+    init: F,
 }
 
 ////////////////////////////////////////////////////////////////////////////////
 //// Implementations
 
-impl<T, F: FnOnce() -> T> LazyStatic<T, F> {
+impl<T, F: Fn() -> T> LazyStatic<T, F> {
     pub const fn new(f: F) -> Self {
         Self {
-            value: UnsafeCell::new(RwLock::new(LazyLock::new(f))),
+            value: RwLock::new(None),
+            init: f,
         }
     }
 
-    pub fn read(&self) -> LockResult<RwLockReadGuard<'_, LazyLock<T, F>>> {
-        unsafe { self.value.as_ref_unchecked().read() }
+    /// Init if it hasn't been
+    pub fn read(&self) -> MappedRwLockReadGuard<'_, T> {
+        loop {
+            if let Ok(guard) =
+                RwLockReadGuard::filter_map(self.lock_read(), Option::as_ref)
+            {
+                break guard;
+            }
+
+            drop(self.write());
+        }
+    }
+
+    /// Init if it hasn't been
+    pub fn write(&self) -> MappedRwLockWriteGuard<'_, T> {
+        RwLockWriteGuard::map(self.lock_write(), |value| {
+            value.get_or_insert_with(&self.init)
+        })
     }
 
-    pub fn write(&self) -> LockResult<RwLockWriteGuard<'_, LazyLock<T, F>>> {
-        unsafe { self.value.as_ref_unchecked().write() }
+    /// Read without init
+    pub fn try_read(&self) -> Option<MappedRwLockReadGuard<'_, T>> {
+        RwLockReadGuard::filter_map(self.lock_read(), Option::as_ref).ok()
+    }
+
+    /// Return the old value if it has been init
+    pub fn replace(&self, value: T) -> Option<T> {
+        self.lock_write().replace(value)
+    }
+
+    /// Run the init function again, return the old value if it has been init
+    pub fn reinit(&self) -> Option<T> {
+        // init outside of the lock, it may read other statics
+        let value = (self.init)();
+
+        self.replace(value)
+    }
+
+    /// Lock is recovered from poison since the value is always replaced
+    /// as a whole
+    fn lock_read(&self) -> RwLockReadGuard<'_, Option<T>> {
+        self.value.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn lock_write(&self) -> RwLockWriteGuard<'_, Option<T>> {
+        self.value.write().unwrap_or_else(PoisonError::into_inner)
     }
 }
 
-unsafe impl<T, F> Sync for LazyStatic<T, F> {}
+impl<T: Debug, F> Debug for LazyStatic<T, F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("LazyStatic").field(&self.value).finish()
+    }
+}
 
 impl<T> OnceStatic<T> {
     pub const fn new() -> Self {
         Self {
-            cell: OnceCell::new(),
+            cell: OnceLock::new(),
         }
     }
 
     ///
     /// # Errors
     ///
-    /// This method returns `Ok(())` if the cell was empty and `Err(value)` if it was full.
+    /// This method returns `Ok(())` if the cell was empty
+    /// and `Err(value)` if it was full.
     ///
     pub fn init(&self, value: T) -> Result<(), T> {
         self.cell.set(value)
     }
+
+    pub fn try_get(&self) -> Option<&T> {
+        self.cell.get()
+    }
+
+    /// Only one of the racing `f` is run
+    pub fn get_or_init(&self, f: impl FnOnce() -> T) -> &T {
+        self.cell.get_or_init(f)
+    }
+
+    /// Test only, (feature `test-util`) take the value out to init again
+    ///
+    /// # Limitation
+    ///
+    /// It takes `&mut self`, so a `static` (only `&`) can't be reset, only
+    /// a local or owned `OnceStatic`. A `&self` reset is impossible as on a
+    /// static `try_get`/`Deref` hand out `&'static T` which would be left
+    /// dangling; use `LazyStatic::replace`/`reinit` for a resettable global.
+    #[cfg(any(test, feature = "test-util"))]
+    pub fn reset(&mut self) -> Option<T> {
+        self.cell.take()
+    }
 }
 
-unsafe impl<T> Sync for OnceStatic<T> {}
+impl<T> Default for OnceStatic<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl<T> Deref for OnceStatic<T> {
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
-        &self.cell.get().expect("uninit")
+        self.cell.get().expect("OnceStatic is used before init")
     }
 }
 
 impl<T> OwnedPtr<T> {
     pub fn new(value: T) -> Self {
-        Self {
-            value: Box::new(value),
-        }
+        Self::from_box(Box::new(value))
     }
 
     pub fn into_inner(owned: Self) -> T {
@@ -121,7 +336,10 @@ impl<T> OwnedPtr<T> {
 
 impl<T: ?Sized> OwnedPtr<T> {
     pub fn from_box(value: Box<T>) -> Self {
-        Self { value }
+        Self {
+            value,
+            token: Token::new(),
+        }
     }
 
     pub fn ptr(&self) -> Ptr<T> {
@@ -129,11 +347,43 @@ impl<T: ?Sized> OwnedPtr<T> {
             value: unsafe {
                 NonNull::new_unchecked(Box::as_ptr(&self.value) as _)
             },
+            generation: self.token.generation(),
         }
     }
-}
 
-unsafe impl<T: Sync> Sync for OwnedPtr<T> {}
+    /// Pointer to write through, it keeps the `Ptr`s given out valid
+    /// (unlike the read only `Ptr` itself)
+    pub(crate) fn as_mut_ptr(this: &mut Self) -> *mut T {
+        Box::as_mut_ptr(&mut this.value)
+    }
+
+    pub fn ptr_mut(&mut self) -> PtrMut<'_, T> {
+        PtrMut {
+            value: NonNull::from(self.value.as_mut()),
+            generation: self.token.generation(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Give up the ownership, the value should be reclaimed by `from_leaked`
+    /// or it's leaked
+    pub fn leak(owned: Self) -> Ptr<T> {
+        Ptr {
+            value: NonNull::from(Box::leak(owned.value)),
+            generation: owned.token.forget(),
+        }
+    }
+
+    /// # Safety
+    ///
+    /// `ptr` should come from `OwnedPtr::leak` and hasn't been reclaimed
+    pub unsafe fn from_leaked(ptr: Ptr<T>) -> Self {
+        Self {
+            value: unsafe { Box::from_raw(ptr.value.as_ptr()) },
+            token: Token::revive(ptr.generation),
+        }
+    }
+}
 
 impl<T: ?Sized> Deref for OwnedPtr<T> {
     type Target = T;
@@ -161,25 +411,72 @@ impl<T: ?Sized> BorrowMut<T> for OwnedPtr<T> {
     }
 }
 
+impl<T: PartialEq + ?Sized> PartialEq for OwnedPtr<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T: Eq + ?Sized> Eq for OwnedPtr<T> {}
+
+impl<T: PartialOrd + ?Sized> PartialOrd for OwnedPtr<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self.value.partial_cmp(&other.value)
+    }
+}
+
+impl<T: Ord + ?Sized> Ord for OwnedPtr<T> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.value.cmp(&other.value)
+    }
+}
+
+impl<T: Hash + ?Sized> Hash for OwnedPtr<T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.value.hash(state);
+    }
+}
+
 impl<T: Debug> Debug for OwnedPtr<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         T::fmt(self, f)
     }
 }
 
-impl<T> Ptr<T> {
+impl<T: ?Sized> Ptr<T> {
     pub fn ptr_eq(this: &Self, other: &Self) -> bool {
-        std::ptr::eq(this.value.as_ptr(), other.value.as_ptr())
+        std::ptr::addr_eq(this.value.as_ptr(), other.value.as_ptr())
+    }
+
+    /// # Safety
+    ///
+    /// No other reference to the value (from any copy of this `Ptr`, or
+    /// from the `OwnedPtr`) is used while the returned one is alive
+    pub unsafe fn as_mut(&mut self) -> &mut T {
+        self.generation.check::<T>();
+
+        unsafe { self.value.as_mut() }
+    }
+
+    /// # Safety
+    ///
+    /// Same as `as_mut` for the whole lifetime `'a`
+    pub unsafe fn into_mut<'a>(self) -> PtrMut<'a, T> {
+        PtrMut {
+            value: self.value,
+            generation: self.generation,
+            _marker: PhantomData,
+        }
     }
 }
 
 unsafe impl<T: Sync + ?Sized> Sync for Ptr<T> {}
 
-unsafe impl<T: ?Sized> Send for Ptr<T> {}
+unsafe impl<T: Sync + ?Sized> Send for Ptr<T> {}
 
 impl<T: ?Sized> Clone for Ptr<T> {
     fn clone(&self) -> Self {
-        Self { value: self.value }
+        *self
     }
 }
 
@@ -189,13 +486,9 @@ impl<T: ?Sized> Deref for Ptr<T> {
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
-        unsafe { self.value.as_ref() }
-    }
-}
+        self.generation.check::<T>();
 
-impl<T: ?Sized> DerefMut for Ptr<T> {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        unsafe { self.value.as_mut() }
+        unsafe { self.value.as_ref() }
     }
 }
 
@@ -205,12 +498,6 @@ impl<T: ?Sized> Borrow<T> for Ptr<T> {
     }
 }
 
-impl<T: ?Sized> BorrowMut<T> for Ptr<T> {
-    fn borrow_mut(&mut self) -> &mut T {
-        self
-    }
-}
-
 impl<T: Debug> Debug for Ptr<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         T::fmt(self, f)
@@ -243,6 +530,378 @@ impl<T: Hash + ?Sized> Hash for Ptr<T> {
     }
 }
 
+impl<'a, T: ?Sized> PtrMut<'a, T> {
+    /// Give up the unique access
+    pub fn into_ptr(this: Self) -> Ptr<T> {
+        Ptr {
+            value: this.value,
+            generation: this.generation,
+        }
+    }
+}
+
+unsafe impl<T: Sync + ?Sized> Sync for PtrMut<'_, T> {}
+
+unsafe impl<T: Send + ?Sized> Send for PtrMut<'_, T> {}
+
+impl<T: ?Sized> Deref for PtrMut<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        self.generation.check::<T>();
+
+        unsafe { self.value.as_ref() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for PtrMut<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.generation.check::<T>();
+
+        unsafe { self.value.as_mut() }
+    }
+}
+
+impl<T: ?Sized> Borrow<T> for PtrMut<'_, T> {
+    fn borrow(&self) -> &T {
+        self
+    }
+}
+
+impl<T: ?Sized> BorrowMut<T> for PtrMut<'_, T> {
+    fn borrow_mut(&mut self) -> &mut T {
+        self
+    }
+}
+
+impl<T: Debug + ?Sized> Debug for PtrMut<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        T::fmt(self, f)
+    }
+}
+
+impl<O, D: Dependent> SelfRef<O, D> {
+    pub fn new(
+        owner: O,
+        build: impl for<'a> FnOnce(&'a O) -> D::Of<'a>,
+    ) -> Self {
+        match Self::try_new(owner, |owner| Ok::<_, ()>(build(owner))) {
+            Ok(it) => it,
+            Err(()) => unreachable!(),
+        }
+    }
+
+    /// The owner is dropped on error
+    pub fn try_new<E>(
+        owner: O,
+        build: impl for<'a> FnOnce(&'a O) -> Result<D::Of<'a>, E>,
+    ) -> Result<Self, E> {
+        let owner = NonNull::from(Box::leak(Box::new(owner)));
+
+        match build(unsafe { owner.as_ref() }) {
+            Ok(dependent) => Ok(Self {
+                dependent: ManuallyDrop::new(unsafe {
+                    extend::<D>(dependent)
+                }),
+                owner,
+                _marker: PhantomData,
+            }),
+            Err(err) => {
+                drop(unsafe { Box::from_raw(owner.as_ptr()) });
+
+                Err(err)
+            }
+        }
+    }
+
+    pub fn owner(&self) -> &O {
+        unsafe { self.owner.as_ref() }
+    }
+
+    pub fn dependent(&self) -> &D::Of<'_> {
+        D::shorten(&self.dependent)
+    }
+
+    pub fn with_dependent<'s, R>(
+        &'s self,
+        f: impl for<'a> FnOnce(&'a O, &'a D::Of<'a>) -> R,
+    ) -> R {
+        f(self.owner(), self.dependent())
+    }
+
+    /// The dependent can be mutated, but only with borrows from the owner
+    pub fn with_dependent_mut<'s, R>(
+        &'s mut self,
+        f: impl for<'a> FnOnce(&'a O, &'a mut D::Of<'a>) -> R,
+    ) -> R {
+        let owner = unsafe { self.owner.as_ref() };
+        let dependent = unsafe {
+            NonNull::from(&mut *self.dependent)
+                .cast::<D::Of<'s>>()
+                .as_mut()
+        };
+
+        f(owner, dependent)
+    }
+
+    /// Drop the dependent, mutate the owner and build the dependent again
+    pub fn rebuild(
+        self,
+        mutate: impl FnOnce(&mut O),
+        build: impl for<'a> FnOnce(&'a O) -> D::Of<'a>,
+    ) -> Self {
+        let mut owner = self.into_owner();
+
+        mutate(&mut owner);
+
+        Self::new(owner, build)
+    }
+
+    pub fn into_owner(self) -> O {
+        let mut this = ManuallyDrop::new(self);
+
+        unsafe {
+            ManuallyDrop::drop(&mut this.dependent);
+
+            *Box::from_raw(this.owner.as_ptr())
+        }
+    }
+}
+
+/// Erase the lifetime of the dependent borrowing from the boxed owner
+unsafe fn extend<D: Dependent>(dependent: D::Of<'_>) -> D::Of<'static> {
+    let dependent = ManuallyDrop::new(dependent);
+
+    unsafe { std::ptr::read(NonNull::from(&*dependent).cast().as_ptr()) }
+}
+
+unsafe impl<O: Send, D: Dependent> Send for SelfRef<O, D> where
+    D::Of<'static>: Send
+{
+}
+
+unsafe impl<O: Sync, D: Dependent> Sync for SelfRef<O, D> where
+    D::Of<'static>: Sync
+{
+}
+
+impl<O, D: Dependent> Drop for SelfRef<O, D> {
+    fn drop(&mut self) {
+        unsafe {
+            ManuallyDrop::drop(&mut self.dependent);
+
+            drop(Box::from_raw(self.owner.as_ptr()));
+        }
+    }
+}
+
+impl<O: Debug, D: Dependent> Debug for SelfRef<O, D>
+where
+    for<'a> D::Of<'a>: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SelfRef")
+            .field("owner", self.owner())
+            .field("dependent", self.dependent())
+            .finish()
+    }
+}
+
 
 ////////////////////////////////////////////////////////////////////////////////
 //// Functions
+
+
+/// Define a `Dependent` type family
+///
+/// `dependent!(pub Tokens<'a> = Vec<&'a str>)`
+#[macro_export]
+macro_rules! dependent {
+    ($vis:vis $name:ident<$lt:lifetime> = $ty:ty) => {
+        $vis struct $name;
+
+        impl $crate::Dependent for $name {
+            type Of<$lt> = $ty;
+
+            fn shorten<'short, 'long: 'short>(
+                dep: &'short Self::Of<'long>,
+            ) -> &'short Self::Of<'short> {
+                dep
+            }
+        }
+    };
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn test_ptr_mut() {
+        let mut owned = OwnedPtr::new(vec![1]);
+
+        owned.ptr_mut().push(2);
+
+        let mut ptr_mut = owned.ptr_mut();
+
+        ptr_mut.push(3);
+
+        let mut ptr = PtrMut::into_ptr(ptr_mut);
+
+        assert_eq!(*ptr, [1, 2, 3]);
+
+        unsafe { ptr.as_mut() }.push(4);
+
+        let mut ptr_mut = unsafe { ptr.into_mut() };
+
+        ptr_mut.push(5);
+
+        assert_eq!(*owned, [1, 2, 3, 4, 5]);
+        assert!(Ptr::ptr_eq(&ptr, &owned.ptr()));
+
+        assert_send_sync::<Ptr<i32>>();
+        assert_send_sync::<PtrMut<'_, i32>>();
+        assert_send_sync::<OwnedPtr<i32>>();
+        assert_send_sync::<PtrList<i32>>();
+    }
+
+    #[test]
+    fn test_once_static() {
+        static ONCE: OnceStatic<String> = OnceStatic::new();
+
+        assert!(ONCE.try_get().is_none());
+
+        let inits = std::sync::atomic::AtomicUsize::new(0);
+
+        std::thread::scope(|scope| {
+            for i in 0..8 {
+                let inits = &inits;
+
+                scope.spawn(move || {
+                    ONCE.get_or_init(|| {
+                        inits
+                            .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                        format!("thread {i}")
+                    })
+                });
+            }
+        });
+
+        assert_eq!(inits.into_inner(), 1);
+        assert!(ONCE.starts_with("thread"));
+        assert_eq!(ONCE.init(String::new()), Err(String::new()));
+
+        let mut local = OnceStatic::<i32>::new();
+
+        local.init(1).unwrap();
+        assert_eq!(local.reset(), Some(1));
+        assert_eq!(*local.get_or_init(|| 2), 2);
+        assert_eq!(*local, 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "used before init")]
+    fn test_once_static_uninit() {
+        let once = OnceStatic::<i32>::new();
+
+        let _ = *once;
+    }
+
+    #[test]
+    fn test_lazy_static() {
+        #[derive(Debug, PartialEq)]
+        struct Config {
+            verbose: bool,
+            level: u32,
+        }
+
+        static CONFIG: LazyStatic<Config> = LazyStatic::new(|| Config {
+            verbose: false,
+            level: 1,
+        });
+
+        assert!(CONFIG.try_read().is_none());
+        assert_eq!(CONFIG.read().level, 1);
+
+        std::thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| CONFIG.write().level += 1);
+            }
+        });
+
+        assert_eq!(CONFIG.read().level, 9);
+
+        let old = CONFIG.replace(Config {
+            verbose: true,
+            level: 0,
+        });
+
+        assert_eq!(old.map(|config| config.level), Some(9));
+        assert!(CONFIG.try_read().unwrap().verbose);
+
+        CONFIG.reinit();
+
+        assert_eq!(
+            *CONFIG.read(),
+            Config {
+                verbose: false,
+                level: 1
+            }
+        );
+    }
+
+    dependent!(Tokens<'a> = Vec<&'a str>);
+
+    #[test]
+    fn test_self_ref() {
+        fn lex(src: &str) -> Vec<&str> {
+            src.split_whitespace().collect()
+        }
+
+        let mut tokens =
+            SelfRef::<String, Tokens>::new("let x = 1".to_owned(), |src| {
+                lex(src)
+            });
+
+        assert_eq!(tokens.owner(), "let x = 1");
+        assert_eq!(tokens.dependent(), &["let", "x", "=", "1"]);
+
+        // moving the container keeps the borrows valid
+        let mut moved = vec![tokens];
+        tokens = moved.pop().unwrap();
+
+        tokens.with_dependent_mut(|src, toks| {
+            toks.retain(|tok| *tok != "=");
+            toks.push(&src[..3]);
+        });
+
+        assert_eq!(
+            tokens.with_dependent(|_, toks| toks.join(" ")),
+            "let x 1 let"
+        );
+
+        tokens = tokens.rebuild(|src| src.push_str(" + 2"), |src| lex(src));
+
+        assert_eq!(tokens.dependent().len(), 6);
+        assert_eq!(
+            format!("{tokens:?}"),
+            concat!(
+                r#"SelfRef { owner: "let x = 1 + 2", "#,
+                r#"dependent: ["let", "x", "=", "1", "+", "2"] }"#
+            )
+        );
+        assert_eq!(tokens.into_owner(), "let x = 1 + 2");
+
+        let res =
+            SelfRef::<String, Tokens>::try_new("1 2 x".to_owned(), |s| {
+                s.split(' ')
+                    .map(|tok| tok.parse::<i32>().map(|_| tok))
+                    .collect::<Result<Vec<_>, _>>()
+            });
+
+        assert!(res.is_err());
+    }
+}
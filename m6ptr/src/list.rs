@@ -0,0 +1,926 @@
+use std::{
+    collections::BTreeSet,
+    fmt,
+    hash::Hash,
+    marker::PhantomData,
+    mem::MaybeUninit,
+    ptr,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use crate::{OwnedPtr, Ptr};
+
+
+////////////////////////////////////////////////////////////////////////////////
+//// Structures
+
+/// Node of `PtrList`, allocated as `OwnedPtr` and linked by `Ptr`
+///
+/// A removed node is kept as an empty shell in the free list of its owner
+/// until the owner drops, so a stale handle never dangles.
+struct Node<T> {
+    /// uninit for a removed shell
+    value: MaybeUninit<T>,
+    prev: Option<Ptr<Node<T>>>,
+    next: Option<Ptr<Node<T>>>,
+    /// bumped on each removal
+    generation: u64,
+    /// of the list allocated the shell
+    segment: u64,
+}
+
+/// Opaque handle of a `PtrList` node, can't be dereferenced,
+/// the value is reached through the list
+pub struct NodeHandle<T> {
+    node: Ptr<Node<T>>,
+    segment: u64,
+    generation: u64,
+}
+
+/// Doubly linked list, nodes are owned by the list and handed out as
+/// `NodeHandle`
+pub struct PtrList<T> {
+    head: Option<Ptr<Node<T>>>,
+    tail: Option<Ptr<Node<T>>>,
+    len: usize,
+    /// removed shells, linked by `next`
+    free: Option<Ptr<Node<T>>>,
+    free_tail: Option<Ptr<Node<T>>>,
+    /// segment of the new shells, 0 for unassigned
+    segment: u64,
+    /// segments of all the owned shells (merged on splice)
+    segments: BTreeSet<u64>,
+    _marker: PhantomData<OwnedPtr<Node<T>>>,
+}
+
+/// Cursor over `PtrList` with a "ghost" position between the tail and the
+/// head (like `std::collections::linked_list::CursorMut`)
+pub struct CursorMut<'a, T> {
+    list: &'a mut PtrList<T>,
+    cur: Option<Ptr<Node<T>>>,
+}
+
+pub struct Iter<'a, T> {
+    head: Option<Ptr<Node<T>>>,
+    tail: Option<Ptr<Node<T>>>,
+    len: usize,
+    _marker: PhantomData<&'a Node<T>>,
+}
+
+pub struct IterMut<'a, T> {
+    head: Option<Ptr<Node<T>>>,
+    tail: Option<Ptr<Node<T>>>,
+    len: usize,
+    _marker: PhantomData<&'a mut Node<T>>,
+}
+
+pub struct IntoIter<T> {
+    list: PtrList<T>,
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+//// Implementations
+
+static NEXT_SEGMENT: AtomicU64 = AtomicU64::new(1);
+
+fn raw<T>(node: Ptr<Node<T>>) -> *mut Node<T> {
+    node.value.as_ptr()
+}
+
+/// # Safety
+///
+/// `node` is linked (not a removed shell)
+unsafe fn value<'a, T>(node: Ptr<Node<T>>) -> &'a T {
+    unsafe { (*raw(node)).value.assume_init_ref() }
+}
+
+/// # Safety
+///
+/// `node` is linked (not a removed shell)
+unsafe fn value_mut<'a, T>(node: Ptr<Node<T>>) -> &'a mut T {
+    unsafe { (*raw(node)).value.assume_init_mut() }
+}
+
+impl<T> NodeHandle<T> {
+    /// # Safety
+    ///
+    /// `node` is alive
+    unsafe fn new(node: Ptr<Node<T>>) -> Self {
+        let Node {
+            segment,
+            generation,
+            ..
+        } = *unsafe { &*raw(node) };
+
+        Self {
+            node,
+            segment,
+            generation,
+        }
+    }
+}
+
+impl<T> Clone for NodeHandle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for NodeHandle<T> {}
+
+impl<T> PartialEq for NodeHandle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        ptr::eq(raw(self.node), raw(other.node))
+            && self.generation == other.generation
+    }
+}
+
+impl<T> Eq for NodeHandle<T> {}
+
+impl<T> Hash for NodeHandle<T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        raw(self.node).hash(state);
+        self.generation.hash(state);
+    }
+}
+
+impl<T> fmt::Debug for NodeHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("NodeHandle")
+            .field(&raw(self.node))
+            .field(&self.generation)
+            .finish()
+    }
+}
+
+unsafe impl<T> Send for NodeHandle<T> {}
+
+unsafe impl<T> Sync for NodeHandle<T> {}
+
+impl<T> PtrList<T> {
+    pub const fn new() -> Self {
+        Self {
+            head: None,
+            tail: None,
+            len: 0,
+            free: None,
+            free_tail: None,
+            segment: 0,
+            segments: BTreeSet::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.head.map(|node| unsafe { value(node) })
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.head.map(|node| unsafe { value_mut(node) })
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.tail.map(|node| unsafe { value(node) })
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.tail.map(|node| unsafe { value_mut(node) })
+    }
+
+    /// Return the handle of the new node
+    pub fn push_front(&mut self, value: T) -> NodeHandle<T> {
+        unsafe { self.link(value, None, self.head) }
+    }
+
+    /// Return the handle of the new node
+    pub fn push_back(&mut self, value: T) -> NodeHandle<T> {
+        unsafe { self.link(value, self.tail, None) }
+    }
+
+    /// Whether the node is in this list (false after it's removed)
+    pub fn contains(&self, node: NodeHandle<T>) -> bool {
+        self.validate(node).is_some()
+    }
+
+    /// O(1) access by handle, None if the node isn't in this list
+    pub fn get(&self, node: NodeHandle<T>) -> Option<&T> {
+        self.validate(node).map(|node| unsafe { value(node) })
+    }
+
+    /// O(1) mutable access by handle, None if the node isn't in this list
+    pub fn get_mut(&mut self, node: NodeHandle<T>) -> Option<&mut T> {
+        self.validate(node).map(|node| unsafe { value_mut(node) })
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.head.map(|node| unsafe { self.unlink(node) })
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.tail.map(|node| unsafe { self.unlink(node) })
+    }
+
+    /// O(1) remove by handle, None if the node isn't in this list
+    pub fn remove(&mut self, node: NodeHandle<T>) -> Option<T> {
+        self.validate(node).map(|node| unsafe { self.unlink(node) })
+    }
+
+    /// O(1) move all nodes of `other` to the back, handles keep valid
+    pub fn append(&mut self, other: &mut Self) {
+        unsafe { self.splice(self.tail, None, other) }
+    }
+
+    /// Removed shells are kept for reuse until the list drops
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            head: self.head,
+            tail: self.tail,
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            head: self.head,
+            tail: self.tail,
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            cur: self.head,
+            list: self,
+        }
+    }
+
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            cur: self.tail,
+            list: self,
+        }
+    }
+
+    /// The shell of an owned segment is never freed before the list,
+    /// so it's safe to read the generation
+    fn validate(&self, node: NodeHandle<T>) -> Option<Ptr<Node<T>>> {
+        let alive = self.segments.contains(&node.segment)
+            && unsafe { (*raw(node.node)).generation } == node.generation;
+
+        alive.then_some(node.node)
+    }
+
+    /// Link new node between `prev` and `next` which should be adjacent
+    unsafe fn link(
+        &mut self,
+        value: T,
+        prev: Option<Ptr<Node<T>>>,
+        next: Option<Ptr<Node<T>>>,
+    ) -> NodeHandle<T> {
+        let node = match self.free {
+            Some(shell) => unsafe {
+                self.free = (*raw(shell)).next;
+
+                if self.free.is_none() {
+                    self.free_tail = None;
+                }
+
+                let node = &mut *raw(shell);
+
+                node.value.write(value);
+                node.prev = prev;
+                node.next = next;
+
+                shell
+            },
+            None => {
+                if self.segment == 0 {
+                    self.segment =
+                        NEXT_SEGMENT.fetch_add(1, Ordering::Relaxed);
+                    self.segments.insert(self.segment);
+                }
+
+                OwnedPtr::leak(OwnedPtr::new(Node {
+                    value: MaybeUninit::new(value),
+                    prev,
+                    next,
+                    generation: 0,
+                    segment: self.segment,
+                }))
+            }
+        };
+
+        unsafe {
+            match prev {
+                Some(prev) => (*raw(prev)).next = Some(node),
+                None => self.head = Some(node),
+            }
+
+            match next {
+                Some(next) => (*raw(next)).prev = Some(node),
+                None => self.tail = Some(node),
+            }
+        }
+
+        self.len += 1;
+
+        unsafe { NodeHandle::new(node) }
+    }
+
+    /// Keep the shell in the free list, invalidating its handles
+    unsafe fn unlink(&mut self, node: Ptr<Node<T>>) -> T {
+        let shell = unsafe { &mut *raw(node) };
+        let (prev, next) = (shell.prev.take(), shell.next.take());
+        let value = unsafe { shell.value.assume_init_read() };
+
+        shell.generation += 1;
+
+        unsafe {
+            match prev {
+                Some(prev) => (*raw(prev)).next = next,
+                None => self.head = next,
+            }
+
+            match next {
+                Some(next) => (*raw(next)).prev = prev,
+                None => self.tail = prev,
+            }
+
+            match self.free_tail {
+                Some(free_tail) => (*raw(free_tail)).next = Some(node),
+                None => self.free = Some(node),
+            }
+        }
+
+        self.free_tail = Some(node);
+        self.len -= 1;
+
+        value
+    }
+
+    /// Move all nodes (with the removed shells) of `other` between `prev`
+    /// and `next` which should be adjacent
+    unsafe fn splice(
+        &mut self,
+        prev: Option<Ptr<Node<T>>>,
+        next: Option<Ptr<Node<T>>>,
+        other: &mut Self,
+    ) {
+        let (Some(other_head), Some(other_tail)) =
+            (other.head.take(), other.tail.take())
+        else {
+            return;
+        };
+
+        unsafe {
+            (*raw(other_head)).prev = prev;
+            (*raw(other_tail)).next = next;
+
+            match prev {
+                Some(prev) => (*raw(prev)).next = Some(other_head),
+                None => self.head = Some(other_head),
+            }
+
+            match next {
+                Some(next) => (*raw(next)).prev = Some(other_tail),
+                None => self.tail = Some(other_tail),
+            }
+
+            if let Some(other_free) = other.free.take() {
+                match self.free_tail {
+                    Some(free_tail) => {
+                        (*raw(free_tail)).next = Some(other_free)
+                    }
+                    None => self.free = Some(other_free),
+                }
+
+                self.free_tail = other.free_tail.take();
+            }
+        }
+
+        self.len += std::mem::take(&mut other.len);
+        self.segments.append(&mut other.segments);
+        other.segment = 0;
+    }
+}
+
+unsafe impl<T: Send> Send for PtrList<T> {}
+
+unsafe impl<T: Sync> Sync for PtrList<T> {}
+
+impl<T> Drop for PtrList<T> {
+    fn drop(&mut self) {
+        self.clear();
+
+        while let Some(shell) = self.free {
+            self.free = unsafe { (*raw(shell)).next };
+
+            drop(unsafe { OwnedPtr::from_leaked(shell) });
+        }
+    }
+}
+
+impl<T> Default for PtrList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for PtrList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T> Extend<T> for PtrList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push_back(value);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for PtrList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T> IntoIterator for PtrList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { list: self }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a PtrList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut PtrList<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<'a, T> CursorMut<'a, T> {
+    /// None on the ghost position
+    pub fn current(&self) -> Option<&T> {
+        self.cur.map(|node| unsafe { value(node) })
+    }
+
+    pub fn current_mut(&mut self) -> Option<&mut T> {
+        self.cur.map(|node| unsafe { value_mut(node) })
+    }
+
+    /// Handle of current node
+    pub fn current_handle(&self) -> Option<NodeHandle<T>> {
+        self.cur.map(|node| unsafe { NodeHandle::new(node) })
+    }
+
+    /// Move to the next node, from the tail to the ghost,
+    /// from the ghost to the head
+    pub fn move_next(&mut self) {
+        self.cur = match self.cur {
+            Some(node) => unsafe { (*raw(node)).next },
+            None => self.list.head,
+        };
+    }
+
+    /// Move to the previous node, from the head to the ghost,
+    /// from the ghost to the tail
+    pub fn move_prev(&mut self) {
+        self.cur = match self.cur {
+            Some(node) => unsafe { (*raw(node)).prev },
+            None => self.list.tail,
+        };
+    }
+
+    pub fn peek_next(&self) -> Option<&T> {
+        self.next_node().map(|node| unsafe { value(node) })
+    }
+
+    pub fn peek_prev(&self) -> Option<&T> {
+        self.prev_node().map(|node| unsafe { value(node) })
+    }
+
+    /// Insert at the back on the ghost position
+    pub fn insert_before(&mut self, value: T) -> NodeHandle<T> {
+        unsafe { self.list.link(value, self.prev_node(), self.cur) }
+    }
+
+    /// Insert at the front on the ghost position
+    pub fn insert_after(&mut self, value: T) -> NodeHandle<T> {
+        unsafe { self.list.link(value, self.cur, self.next_node()) }
+    }
+
+    /// Remove current node and move to the next
+    pub fn remove_current(&mut self) -> Option<T> {
+        let node = self.cur?;
+
+        self.cur = unsafe { (*raw(node)).next };
+
+        Some(unsafe { self.list.unlink(node) })
+    }
+
+    /// O(1) move all nodes of `other` after current node
+    /// (at the front on the ghost position)
+    pub fn splice_after(&mut self, other: &mut PtrList<T>) {
+        unsafe { self.list.splice(self.cur, self.next_node(), other) }
+    }
+
+    /// O(1) move all nodes of `other` before current node
+    /// (at the back on the ghost position)
+    pub fn splice_before(&mut self, other: &mut PtrList<T>) {
+        unsafe { self.list.splice(self.prev_node(), self.cur, other) }
+    }
+
+    fn next_node(&self) -> Option<Ptr<Node<T>>> {
+        match self.cur {
+            Some(node) => unsafe { (*raw(node)).next },
+            None => self.list.head,
+        }
+    }
+
+    fn prev_node(&self) -> Option<Ptr<Node<T>>> {
+        match self.cur {
+            Some(node) => unsafe { (*raw(node)).prev },
+            None => self.list.tail,
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+
+        self.head.map(|node| unsafe {
+            self.len -= 1;
+            self.head = (*raw(node)).next;
+
+            value(node)
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+
+        self.tail.map(|node| unsafe {
+            self.len -= 1;
+            self.tail = (*raw(node)).prev;
+
+            value(node)
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+
+        self.head.map(|node| unsafe {
+            self.len -= 1;
+            self.head = (*raw(node)).next;
+
+            value_mut(node)
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IterMut<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+
+        self.tail.map(|node| unsafe {
+            self.len -= 1;
+            self.tail = (*raw(node)).prev;
+
+            value_mut(node)
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
+unsafe impl<T: Send> Send for IterMut<'_, T> {}
+
+unsafe impl<T: Sync> Sync for IterMut<'_, T> {}
+
+unsafe impl<T: Send> Send for CursorMut<'_, T> {}
+
+unsafe impl<T: Sync> Sync for CursorMut<'_, T> {}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.list.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.list.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::DropCounter;
+
+    fn to_vec<T: Clone>(list: &PtrList<T>) -> Vec<T> {
+        list.iter().cloned().collect()
+    }
+
+    #[test]
+    fn test_ptr_list() {
+        let mut list = PtrList::new();
+
+        let n2 = list.push_back(2);
+        let n1 = list.push_front(1);
+        let n3 = list.push_back(3);
+
+        assert_eq!(list.len(), 3);
+        assert_eq!(to_vec(&list), [1, 2, 3]);
+        assert_eq!(list.iter().rev().cloned().collect::<Vec<_>>(), [3, 2, 1]);
+        assert_eq!((list.front(), list.back()), (Some(&1), Some(&3)));
+        assert_eq!(list.get(n1), Some(&1));
+
+        *list.get_mut(n1).unwrap() += 10;
+        assert_eq!(list.front(), Some(&11));
+        *list.get_mut(n1).unwrap() -= 10;
+
+        assert_eq!(list.remove(n2), Some(2));
+        assert_eq!(to_vec(&list), [1, 3]);
+        assert_eq!(list.remove(n3), Some(3));
+        assert_eq!(list.back(), Some(&1));
+
+        for x in &mut list {
+            *x *= 10;
+        }
+
+        *list.back_mut().unwrap() += 1;
+
+        assert_eq!(list.pop_back(), Some(11));
+        assert_eq!(list.pop_front(), None);
+        assert!(list.is_empty());
+
+        list.extend(0..5);
+
+        let mut iter = list.iter();
+
+        assert_eq!(iter.len(), 5);
+        assert_eq!(iter.next(), Some(&0));
+        assert_eq!(iter.next_back(), Some(&4));
+        assert_eq!(iter.len(), 3);
+        assert_eq!(iter.collect::<Vec<_>>(), [&1, &2, &3]);
+
+        assert_eq!(
+            list.into_iter().rev().collect::<Vec<_>>(),
+            [4, 3, 2, 1, 0]
+        );
+    }
+
+    #[test]
+    fn test_ptr_list_splice() {
+        let mut list = (0..3).collect::<PtrList<_>>();
+        let mut other = (10..13).collect::<PtrList<_>>();
+        let n11 = other.cursor_front_mut().insert_after(100);
+
+        list.append(&mut other);
+
+        assert!(other.is_empty());
+        assert_eq!(list.len(), 7);
+        assert!(!other.contains(n11));
+        assert_eq!(to_vec(&list), [0, 1, 2, 10, 100, 11, 12]);
+        assert_eq!(list.remove(n11), Some(100));
+
+        let mut cursor = list.cursor_front_mut();
+        cursor.move_next();
+
+        let mut other = (20..22).collect::<PtrList<_>>();
+        cursor.splice_after(&mut other);
+        assert_eq!(cursor.peek_next(), Some(&20));
+
+        let mut other = (30..32).collect::<PtrList<_>>();
+        cursor.splice_before(&mut other);
+        assert_eq!(cursor.peek_prev(), Some(&31));
+
+        // ghost position
+        cursor.move_prev();
+        cursor.move_prev();
+        cursor.move_prev();
+        cursor.move_prev();
+        assert_eq!(cursor.current(), None);
+
+        let mut other = (40..41).collect::<PtrList<_>>();
+        cursor.splice_after(&mut other);
+        let mut other = (50..51).collect::<PtrList<_>>();
+        cursor.splice_before(&mut other);
+
+        assert_eq!(
+            to_vec(&list),
+            [40, 0, 30, 31, 1, 20, 21, 2, 10, 11, 12, 50]
+        );
+        assert_eq!(list.len(), 12);
+
+        list.append(&mut PtrList::new());
+        assert_eq!(list.len(), 12);
+    }
+
+    #[test]
+    fn test_ptr_list_cursor() {
+        let mut list = (1..=5).collect::<PtrList<_>>();
+        let mut cursor = list.cursor_front_mut();
+
+        // remove the even ones
+        while let Some(&x) = cursor.current() {
+            if x % 2 == 0 {
+                assert_eq!(cursor.remove_current(), Some(x));
+            }
+            else {
+                *cursor.current_mut().unwrap() *= 10;
+                cursor.move_next();
+            }
+        }
+
+        assert_eq!(cursor.remove_current(), None);
+        assert_eq!(cursor.peek_next(), Some(&10));
+        assert_eq!(cursor.peek_prev(), Some(&50));
+
+        cursor.insert_after(0);
+        cursor.insert_before(60);
+        cursor.move_next();
+        assert_eq!(cursor.current(), Some(&0));
+
+        let n = cursor.insert_after(5);
+        cursor.move_prev();
+        assert!(cursor.current().is_none());
+        cursor.move_prev();
+        cursor.insert_before(55);
+
+        let handle = cursor.current_handle().unwrap();
+
+        assert_ne!(handle, n);
+        assert_eq!(list.get(handle), Some(&60));
+        assert_eq!(list.get(n), Some(&5));
+        assert_eq!(to_vec(&list), [0, 5, 10, 30, 50, 55, 60]);
+
+        let mut cursor = list.cursor_back_mut();
+
+        assert_eq!(cursor.current(), Some(&60));
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.current(), Some(&0));
+    }
+
+    #[test]
+    fn test_ptr_list_stale_handle() {
+        let mut list = PtrList::new();
+        let n1 = list.push_back(1);
+
+        assert_eq!(list.pop_back(), Some(1));
+        assert!(!list.contains(n1));
+        assert_eq!(list.get(n1), None);
+        assert_eq!(list.remove(n1), None);
+
+        // reuse the removed shell
+        let n2 = list.push_back(2);
+
+        assert_ne!(n1, n2);
+        assert_eq!(list.get(n1), None);
+        assert_eq!(list.get_mut(n1), None);
+        assert_eq!(list.remove(n1), None);
+        assert_eq!(list.remove(n2), Some(2));
+        assert_eq!(list.remove(n2), None);
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn test_ptr_list_foreign_handle() {
+        let mut list = PtrList::new();
+        let mut other = PtrList::new();
+
+        let n1 = list.push_back(1);
+        let n2 = other.push_back(2);
+
+        assert_eq!(list.get(n2), None);
+        assert_eq!(other.get_mut(n1), None);
+        assert_eq!(list.remove(n2), None);
+        assert_eq!(other.len(), 1);
+
+        drop(other);
+        assert_eq!(list.get(n2), None);
+
+        // moved nodes (and removed shells) belong to the new owner
+        let mut other = PtrList::new();
+        let n3 = other.push_back(3);
+        let n4 = other.push_back(4);
+
+        other.pop_front();
+        list.append(&mut other);
+
+        assert_eq!(list.get(n4), Some(&4));
+        assert_eq!(list.get(n3), None);
+        assert_eq!(other.get(n4), None);
+
+        // `other` allocates a new segment
+        let n5 = other.push_back(5);
+
+        assert_eq!(list.get(n5), None);
+        assert_eq!(other.get(n5), Some(&5));
+        assert_eq!(to_vec(&list), [1, 4]);
+    }
+
+    #[test]
+    fn test_ptr_list_drop() {
+        let drops = DropCounter::new();
+
+        {
+            let mut list = PtrList::new();
+
+            for _ in 0..10 {
+                list.push_back(drops.make());
+            }
+
+            list.pop_front();
+            assert_eq!(drops.get(), 1);
+
+            let mut cursor = list.cursor_back_mut();
+            cursor.remove_current();
+            assert_eq!(drops.get(), 2);
+        }
+
+        assert_eq!(drops.get(), 10);
+
+        let mut iter = (0..10)
+            .map(|_| drops.make())
+            .collect::<PtrList<_>>()
+            .into_iter();
+
+        iter.next();
+        drop(iter);
+
+        assert_eq!(drops.get(), 20);
+    }
+}
@@ -0,0 +1,152 @@
+//! Debug liveness check of `Ptr` (feature `liveness-check`)
+//!
+//! Each `OwnedPtr` registers a unique generation which is copied into every
+//! `Ptr` derived from it, and `Ptr` checks that the generation is still
+//! registered on dereference.
+//!
+//! Without the feature (or in release) all of these are zero-sized no-ops.
+
+#[cfg(all(feature = "liveness-check", debug_assertions))]
+use std::{
+    collections::BTreeSet,
+    sync::{
+        Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+
+////////////////////////////////////////////////////////////////////////////////
+//// Structures
+
+/// Generation of an `OwnedPtr` allocation, carried by `Ptr`
+#[derive(Clone, Copy)]
+pub(crate) struct Generation {
+    #[cfg(all(feature = "liveness-check", debug_assertions))]
+    id: u64,
+}
+
+/// Keep generation registered until dropped, held by `OwnedPtr`
+pub(crate) struct Token {
+    generation: Generation,
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+//// Implementations
+
+#[cfg(all(feature = "liveness-check", debug_assertions))]
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+#[cfg(all(feature = "liveness-check", debug_assertions))]
+static LIVE: Mutex<BTreeSet<u64>> = Mutex::new(BTreeSet::new());
+
+#[cfg(all(feature = "liveness-check", debug_assertions))]
+fn live() -> std::sync::MutexGuard<'static, BTreeSet<u64>> {
+    LIVE.lock().unwrap_or_else(|poison| poison.into_inner())
+}
+
+impl Generation {
+    /// Panic if the owner has been dropped
+    #[inline(always)]
+    pub(crate) fn check<T: ?Sized>(self) {
+        #[cfg(all(feature = "liveness-check", debug_assertions))]
+        if !live().contains(&self.id) {
+            panic!(
+                "dangling Ptr<{}>: its OwnedPtr (generation {}) \
+                 has been dropped",
+                std::any::type_name::<T>(),
+                self.id
+            );
+        }
+    }
+}
+
+impl Token {
+    pub(crate) fn new() -> Self {
+        #[cfg(all(feature = "liveness-check", debug_assertions))]
+        {
+            let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+
+            live().insert(id);
+
+            Self {
+                generation: Generation { id },
+            }
+        }
+
+        #[cfg(not(all(feature = "liveness-check", debug_assertions)))]
+        Self {
+            generation: Generation {},
+        }
+    }
+
+    pub(crate) fn generation(&self) -> Generation {
+        self.generation
+    }
+
+    /// Keep the generation registered forever (the value is leaked)
+    pub(crate) fn forget(self) -> Generation {
+        std::mem::ManuallyDrop::new(self).generation
+    }
+
+    /// Take back the ownership of a forgotten generation
+    pub(crate) fn revive(generation: Generation) -> Self {
+        Self { generation }
+    }
+}
+
+#[cfg(all(feature = "liveness-check", debug_assertions))]
+impl Drop for Token {
+    fn drop(&mut self) {
+        live().remove(&self.generation.id);
+    }
+}
+
+
+#[cfg(all(test, feature = "liveness-check", debug_assertions))]
+mod tests {
+    use crate::{Arena, OwnedPtr};
+
+    #[test]
+    fn test_live_ptr() {
+        let owned = OwnedPtr::new(vec![1, 2, 3]);
+        let ptr = owned.ptr();
+
+        assert_eq!(ptr.len(), 3);
+
+        let leaked = OwnedPtr::leak(owned);
+
+        assert_eq!(ptr.len(), 3);
+
+        let owned = unsafe { OwnedPtr::from_leaked(leaked) };
+
+        assert_eq!(ptr.len(), 3);
+        assert_eq!(OwnedPtr::into_inner(owned), [1, 2, 3]);
+    }
+
+    #[test]
+    #[should_panic(expected = "dangling Ptr<alloc::string::String>")]
+    fn test_dangling_ptr() {
+        let owned = OwnedPtr::new(String::from("abc"));
+        let ptr = owned.ptr();
+
+        drop(owned);
+
+        let _ = ptr.len();
+    }
+
+    #[test]
+    #[should_panic(expected = "dangling Ptr<i32>")]
+    fn test_dangling_arena_ptr() {
+        let mut arena = Arena::new();
+        let ptr = arena.alloc(1);
+
+        assert_eq!(*ptr, 1);
+
+        arena.reset();
+        arena.alloc(2);
+
+        let _ = *ptr;
+    }
+}
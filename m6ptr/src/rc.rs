@@ -0,0 +1,336 @@
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    ops::Deref,
+    ptr::NonNull,
+    rc::{self, Rc},
+    sync::{self, Arc},
+};
+
+use crate::{Ptr, liveness::Token};
+
+
+////////////////////////////////////////////////////////////////////////////////
+//// Structures
+
+struct Inner<T> {
+    token: Token,
+    value: T,
+}
+
+/// Shared ownership (`Rc`) =derive=> Ptr
+///
+/// # Note
+///
+/// derive by value T instead of pointer to T
+pub struct RcPtr<T> {
+    inner: Rc<Inner<T>>,
+}
+
+pub struct WeakRcPtr<T> {
+    inner: rc::Weak<Inner<T>>,
+}
+
+/// Thread-safe shared ownership (`Arc`) =derive=> Ptr
+///
+/// # Note
+///
+/// derive by value T instead of pointer to T
+pub struct ArcPtr<T> {
+    inner: Arc<Inner<T>>,
+}
+
+pub struct WeakArcPtr<T> {
+    inner: sync::Weak<Inner<T>>,
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+//// Implementations
+
+macro_rules! impl_shared_ptr {
+    ($($name:ident, $weak:ident: $rc:ident, $rc_weak:ty);+) => {
+        $(
+            impl<T> $name<T> {
+                pub fn new(value: T) -> Self {
+                    Self {
+                        inner: $rc::new(Inner {
+                            token: Token::new(),
+                            value,
+                        }),
+                    }
+                }
+
+                /// Non-owning handle, valid while any strong one is alive
+                pub fn ptr(this: &Self) -> Ptr<T> {
+                    Ptr {
+                        value: NonNull::from(&this.inner.value),
+                        generation: this.inner.token.generation(),
+                    }
+                }
+
+                pub fn downgrade(this: &Self) -> $weak<T> {
+                    $weak {
+                        inner: $rc::downgrade(&this.inner),
+                    }
+                }
+
+                pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+                    $rc::ptr_eq(&this.inner, &other.inner)
+                }
+
+                ///
+                /// # Errors
+                ///
+                /// Return `Err(this)` if it isn't the only strong one.
+                ///
+                pub fn try_unwrap(this: Self) -> Result<T, Self> {
+                    $rc::try_unwrap(this.inner)
+                        .map(|inner| inner.value)
+                        .map_err(|inner| Self { inner })
+                }
+
+                /// Some if it's the only one (strong or weak)
+                pub fn get_mut(this: &mut Self) -> Option<&mut T> {
+                    $rc::get_mut(&mut this.inner).map(|inner| &mut inner.value)
+                }
+
+                pub fn strong_count(this: &Self) -> usize {
+                    $rc::strong_count(&this.inner)
+                }
+
+                pub fn weak_count(this: &Self) -> usize {
+                    $rc::weak_count(&this.inner)
+                }
+            }
+
+            impl<T> $weak<T> {
+                /// Never upgrade
+                pub fn new() -> Self {
+                    Self {
+                        inner: <$rc_weak>::new(),
+                    }
+                }
+
+                pub fn upgrade(&self) -> Option<$name<T>> {
+                    self.inner.upgrade().map(|inner| $name { inner })
+                }
+
+                pub fn ptr_eq(&self, other: &Self) -> bool {
+                    self.inner.ptr_eq(&other.inner)
+                }
+            }
+
+            impl<T> Clone for $name<T> {
+                fn clone(&self) -> Self {
+                    Self {
+                        inner: self.inner.clone(),
+                    }
+                }
+            }
+
+            impl<T> Clone for $weak<T> {
+                fn clone(&self) -> Self {
+                    Self {
+                        inner: self.inner.clone(),
+                    }
+                }
+            }
+
+            impl<T> Default for $weak<T> {
+                fn default() -> Self {
+                    Self::new()
+                }
+            }
+
+            impl<T> From<T> for $name<T> {
+                fn from(value: T) -> Self {
+                    Self::new(value)
+                }
+            }
+
+            impl<T> Deref for $name<T> {
+                type Target = T;
+
+                fn deref(&self) -> &Self::Target {
+                    &self.inner.value
+                }
+            }
+
+            impl<T> Borrow<T> for $name<T> {
+                fn borrow(&self) -> &T {
+                    self
+                }
+            }
+
+            impl<T: fmt::Debug> fmt::Debug for $name<T> {
+                fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    T::fmt(self, f)
+                }
+            }
+
+            impl<T> fmt::Debug for $weak<T> {
+                fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    write!(f, "(Weak)")
+                }
+            }
+
+            impl<T: PartialEq> PartialEq for $name<T> {
+                fn eq(&self, other: &Self) -> bool {
+                    self.deref() == other.deref()
+                }
+            }
+
+            impl<T: Eq> Eq for $name<T> {}
+
+            impl<T: PartialOrd> PartialOrd for $name<T> {
+                fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                    self.deref().partial_cmp(other.deref())
+                }
+            }
+
+            impl<T: Ord> Ord for $name<T> {
+                fn cmp(&self, other: &Self) -> Ordering {
+                    self.deref().cmp(other.deref())
+                }
+            }
+
+            impl<T: Hash> Hash for $name<T> {
+                fn hash<H: Hasher>(&self, state: &mut H) {
+                    self.deref().hash(state);
+                }
+            }
+        )+
+    };
+}
+
+impl_shared_ptr!(
+    RcPtr, WeakRcPtr: Rc, rc::Weak<Inner<T>>;
+    ArcPtr, WeakArcPtr: Arc, sync::Weak<Inner<T>>
+);
+
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, collections::HashSet};
+
+    use super::*;
+
+    #[derive(Debug)]
+    struct TreeNode {
+        key: i32,
+        parent: RefCell<WeakRcPtr<TreeNode>>,
+        children: RefCell<Vec<RcPtr<TreeNode>>>,
+    }
+
+    impl TreeNode {
+        fn new(key: i32) -> RcPtr<Self> {
+            RcPtr::new(Self {
+                key,
+                parent: RefCell::default(),
+                children: RefCell::default(),
+            })
+        }
+    }
+
+    #[test]
+    fn test_rc_ptr() {
+        let root = TreeNode::new(0);
+
+        for i in 1..=3 {
+            let child = TreeNode::new(i);
+
+            *child.parent.borrow_mut() = RcPtr::downgrade(&root);
+            root.children.borrow_mut().push(child);
+        }
+
+        assert_eq!(RcPtr::strong_count(&root), 1);
+        assert_eq!(RcPtr::weak_count(&root), 3);
+
+        let children = root.children.borrow();
+        let parent = children[1].parent.borrow().upgrade().unwrap();
+
+        assert!(RcPtr::ptr_eq(&parent, &root));
+
+        // fast traversal without refcount
+        let ptrs = children.iter().map(RcPtr::ptr).collect::<Vec<_>>();
+
+        assert_eq!(ptrs.iter().map(|ptr| ptr.key).sum::<i32>(), 6);
+        assert_eq!(RcPtr::strong_count(&children[0]), 1);
+
+        drop(children);
+
+        assert!(RcPtr::try_unwrap(root).is_err());
+        drop(parent);
+
+        let weak = WeakRcPtr::<i32>::new();
+
+        assert!(weak.upgrade().is_none());
+
+        let mut one = RcPtr::new(1);
+
+        *RcPtr::get_mut(&mut one).unwrap() += 1;
+
+        let weak = RcPtr::downgrade(&one);
+
+        assert!(RcPtr::get_mut(&mut one).is_none());
+        assert_eq!(RcPtr::try_unwrap(one), Ok(2));
+        assert!(weak.upgrade().is_none());
+    }
+
+    #[test]
+    fn test_shared_ptr_by_value() {
+        let set = [RcPtr::new("a"), RcPtr::new("b"), RcPtr::new("a")]
+            .into_iter()
+            .collect::<HashSet<_>>();
+
+        assert_eq!(set.len(), 2);
+        assert!(set.contains(&"a"));
+        assert!(RcPtr::new(1) < RcPtr::new(2));
+        assert_eq!(format!("{:?}", ArcPtr::new([1, 2])), "[1, 2]");
+    }
+
+    #[test]
+    fn test_arc_ptr() {
+        let shared = ArcPtr::new(vec![1, 2, 3]);
+
+        let sums = std::thread::scope(|scope| {
+            (0..4)
+                .map(|_| {
+                    let shared = shared.clone();
+
+                    scope.spawn(move || shared.iter().sum::<i32>())
+                })
+                .collect::<Vec<_>>()
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+
+        assert_eq!(sums, [6; 4]);
+        assert_eq!(ArcPtr::strong_count(&shared), 1);
+
+        let weak = ArcPtr::downgrade(&shared);
+
+        assert!(weak.upgrade().is_some());
+        assert_eq!(ArcPtr::try_unwrap(shared).unwrap(), [1, 2, 3]);
+        assert!(weak.upgrade().is_none());
+    }
+
+    #[cfg(all(feature = "liveness-check", debug_assertions))]
+    #[test]
+    #[should_panic(expected = "dangling Ptr")]
+    fn test_rc_ptr_dangling() {
+        let rc = RcPtr::new(1);
+        let ptr = RcPtr::ptr(&rc);
+        let other = rc.clone();
+
+        drop(rc);
+        assert_eq!(*ptr, 1);
+        drop(other);
+
+        let _ = *ptr;
+    }
+}
@@ -0,0 +1,29 @@
+//! Test fixtures shared by the modules
+
+use std::{cell::Cell, rc::Rc};
+
+/// Count the drops of the values it makes
+#[derive(Clone, Default)]
+pub(crate) struct DropCounter(Rc<Cell<usize>>);
+
+pub(crate) struct Counted(Rc<Cell<usize>>);
+
+impl DropCounter {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn make(&self) -> Counted {
+        Counted(self.0.clone())
+    }
+
+    pub(crate) fn get(&self) -> usize {
+        self.0.get()
+    }
+}
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
@@ -0,0 +1,321 @@
+use std::fmt;
+use std::mem::ManuallyDrop;
+use std::ptr;
+use std::sync::atomic::Ordering::{Acquire, Relaxed, Release};
+
+use crossbeam_epoch::{self as epoch, Atomic, Owned};
+
+////////////////////////////////////////////////////////////////////////////////
+/////// ConcurrentStack
+
+/// Lock-free Treiber stack, nodes are reclaimed by epoch
+pub struct ConcurrentStack<T> {
+    head: Atomic<Node<T>>,
+}
+
+struct Node<T> {
+    value: ManuallyDrop<T>,
+    next: Atomic<Node<T>>,
+}
+
+impl<T> ConcurrentStack<T> {
+    pub fn new() -> Self {
+        Self {
+            head: Atomic::null(),
+        }
+    }
+
+    pub fn push(&self, item: T) {
+        let mut node = Owned::new(Node {
+            value: ManuallyDrop::new(item),
+            next: Atomic::null(),
+        });
+        let guard = &epoch::pin();
+
+        loop {
+            let head = self.head.load(Relaxed, guard);
+            node.next.store(head, Relaxed);
+
+            match self
+                .head
+                .compare_exchange(head, node, Release, Relaxed, guard)
+            {
+                Ok(_) => break,
+                Err(err) => node = err.new,
+            }
+        }
+    }
+
+    pub fn pop(&self) -> Option<T> {
+        let guard = &epoch::pin();
+
+        loop {
+            let head = self.head.load(Acquire, guard);
+            let node = unsafe { head.as_ref() }?;
+            let next = node.next.load(Relaxed, guard);
+
+            if self
+                .head
+                .compare_exchange(head, next, Relaxed, Relaxed, guard)
+                .is_ok()
+            {
+                // Safety: the node is unlinked by us, so the value is read
+                // exactly once and the node is freed after all readers unpin
+                unsafe {
+                    guard.defer_destroy(head);
+
+                    return Some(ManuallyDrop::into_inner(ptr::read(
+                        &node.value,
+                    )));
+                }
+            }
+        }
+    }
+
+    /// It's just a snapshot under concurrency
+    pub fn is_empty(&self) -> bool {
+        let guard = &epoch::pin();
+
+        self.head.load(Acquire, guard).is_null()
+    }
+}
+
+impl<T> Default for ConcurrentStack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for ConcurrentStack<T> {
+    fn drop(&mut self) {
+        // Safety: we have `&mut self`, no other thread can access the nodes
+        unsafe {
+            let guard = epoch::unprotected();
+            let mut cur = self.head.load(Relaxed, guard);
+
+            while !cur.is_null() {
+                let mut node = cur.into_owned();
+
+                cur = node.next.load(Relaxed, guard);
+                ManuallyDrop::drop(&mut node.value);
+            }
+        }
+    }
+}
+
+impl<T> fmt::Debug for ConcurrentStack<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConcurrentStack").finish_non_exhaustive()
+    }
+}
+
+/// Items are moved across threads but never shared
+unsafe impl<T: Send> Send for ConcurrentStack<T> {}
+
+unsafe impl<T: Send> Sync for ConcurrentStack<T> {}
+
+
+#[cfg(all(test, not(loom)))]
+mod test {
+    use std::sync::atomic::{AtomicUsize, Ordering::SeqCst};
+    use std::sync::{Arc, Barrier};
+    use std::thread;
+
+    use super::*;
+    use crate::test_util::DropCounter;
+
+    const THREADS: usize = 8;
+    const ITEMS: usize = 20_000;
+
+    #[test]
+    fn test_concurrent_stack_seq() {
+        let stack = ConcurrentStack::new();
+
+        assert!(stack.is_empty());
+        assert_eq!(stack.pop(), None);
+
+        stack.push(1);
+        stack.push(2);
+        stack.push(3);
+
+        assert!(!stack.is_empty());
+        assert_eq!(stack.pop(), Some(3));
+        assert_eq!(stack.pop(), Some(2));
+        assert_eq!(stack.pop(), Some(1));
+        assert_eq!(stack.pop(), None);
+    }
+
+    #[test]
+    fn test_concurrent_stack_push_pop_stress() {
+        let stack = Arc::new(ConcurrentStack::new());
+        let barrier = Arc::new(Barrier::new(THREADS * 2));
+        let popped = Arc::new(AtomicUsize::new(0));
+        let popped_sum = Arc::new(AtomicUsize::new(0));
+
+        let mut handles = vec![];
+
+        for t in 0..THREADS {
+            let stack = stack.clone();
+            let barrier = barrier.clone();
+
+            handles.push(thread::spawn(move || {
+                barrier.wait();
+
+                for i in 0..ITEMS {
+                    stack.push(t * ITEMS + i);
+                }
+            }));
+        }
+
+        for _ in 0..THREADS {
+            let stack = stack.clone();
+            let barrier = barrier.clone();
+            let popped = popped.clone();
+            let popped_sum = popped_sum.clone();
+
+            handles.push(thread::spawn(move || {
+                barrier.wait();
+
+                for _ in 0..ITEMS {
+                    if let Some(x) = stack.pop() {
+                        popped.fetch_add(1, SeqCst);
+                        popped_sum.fetch_add(x, SeqCst);
+                    }
+                }
+            }));
+        }
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        while let Some(x) = stack.pop() {
+            popped.fetch_add(1, SeqCst);
+            popped_sum.fetch_add(x, SeqCst);
+        }
+
+        let n = THREADS * ITEMS;
+
+        assert_eq!(popped.load(SeqCst), n);
+        assert_eq!(popped_sum.load(SeqCst), n * (n - 1) / 2);
+        assert!(stack.is_empty());
+    }
+
+    #[test]
+    fn test_concurrent_stack_drop() {
+        let drops = DropCounter::new();
+        let stack = Arc::new(ConcurrentStack::new());
+
+        let handles = (0..THREADS)
+            .map(|_| {
+                let stack = stack.clone();
+                let drops = drops.clone();
+
+                thread::spawn(move || {
+                    for i in 0..ITEMS {
+                        stack.push(drops.make());
+
+                        if i % 3 == 0 {
+                            stack.pop();
+                        }
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let popped = THREADS * ITEMS.div_ceil(3);
+
+        assert_eq!(drops.get(), popped);
+
+        drop(stack);
+
+        assert_eq!(drops.get(), THREADS * ITEMS);
+    }
+}
+
+
+/// Loom model check of push/pop interleavings, run with
+/// `RUSTFLAGS="--cfg loom --cfg crossbeam_loom" \
+/// cargo test --features concurrent --release loom`
+#[cfg(all(test, loom))]
+mod loom_test {
+    use loom::sync::Arc;
+    use loom::thread;
+
+    use super::*;
+
+    /// Epoch pinning makes the full state space intractable, so bound the
+    /// number of preemptions per execution
+    fn model<F: Fn() + Sync + Send + 'static>(f: F) {
+        let mut builder = loom::model::Builder::new();
+        builder.preemption_bound = Some(2);
+        builder.check(f);
+    }
+
+    #[test]
+    fn loom_concurrent_push_pop() {
+        model(|| {
+            let stack = Arc::new(ConcurrentStack::new());
+
+            let pusher = {
+                let stack = stack.clone();
+
+                thread::spawn(move || {
+                    stack.push(1);
+                    stack.push(2);
+                })
+            };
+
+            let popper = {
+                let stack = stack.clone();
+
+                thread::spawn(move || stack.pop())
+            };
+
+            pusher.join().unwrap();
+
+            let mut popped =
+                popper.join().unwrap().into_iter().collect::<Vec<_>>();
+
+            while let Some(x) = stack.pop() {
+                popped.push(x);
+            }
+
+            popped.sort_unstable();
+
+            assert_eq!(popped, [1, 2]);
+        });
+    }
+
+    #[test]
+    fn loom_concurrent_pop_pop() {
+        model(|| {
+            let stack = Arc::new(ConcurrentStack::new());
+
+            stack.push(1);
+            stack.push(2);
+
+            let poppers = (0..2)
+                .map(|_| {
+                    let stack = stack.clone();
+
+                    thread::spawn(move || stack.pop())
+                })
+                .collect::<Vec<_>>();
+
+            let mut popped = poppers
+                .into_iter()
+                .map(|handle| handle.join().unwrap().unwrap())
+                .collect::<Vec<_>>();
+
+            popped.sort_unstable();
+
+            assert_eq!(popped, [1, 2]);
+            assert!(stack.is_empty());
+        });
+    }
+}
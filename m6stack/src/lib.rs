@@ -1,20 +1,79 @@
+// the tests call `extend(vec.into_iter())` explicitly
+#![cfg_attr(test, allow(clippy::useless_conversion))]
+
+use std::collections::{vec_deque, VecDeque};
 use std::fmt;
+use std::hash::{Hash, Hasher};
 use std::iter;
-use std::iter::Rev;
+use std::iter::{FusedIterator, Rev};
+use std::ops::{Deref, DerefMut};
+use std::rc::Rc;
+use std::slice;
+use std::sync::atomic::{AtomicU64, Ordering::Relaxed};
 use std::vec;
 
+#[cfg(feature = "concurrent")]
+mod concurrent;
+
+#[cfg(feature = "concurrent")]
+pub use concurrent::ConcurrentStack;
+
+#[cfg(all(test, feature = "concurrent", not(loom)))]
+mod test_util;
+
 ////////////////////////////////////////////////////////////////////////////////
 /////// Stack
 
 #[derive(Clone)]
 pub struct Stack<T> {
     _value_vec: Vec<T>,
+    /// stack length of each mark, non-descending
+    _marks: Vec<usize>,
+    /// epoch of each mark, tell a live mark from a released one
+    /// which index has been reused (or from a mark of another stack)
+    _mark_epochs: Vec<u64>,
+}
+
+/// Epochs are unique among all stacks
+static NEXT_MARK_EPOCH: AtomicU64 = AtomicU64::new(0);
+
+/// Frame boundary of Stack
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StackMark {
+    idx: usize,
+    epoch: u64,
+}
+
+/// Roll back the stack to the mark on drop
+pub struct StackGuard<'a, T> {
+    stack: &'a mut Stack<T>,
+    mark: StackMark,
 }
 
 impl<T> Stack<T> {
     // staic method
     pub fn new() -> Self {
-        Self { _value_vec: vec![] }
+        Self {
+            _value_vec: vec![],
+            _marks: vec![],
+            _mark_epochs: vec![],
+        }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            _value_vec: Vec::with_capacity(capacity),
+            _marks: vec![],
+            _mark_epochs: vec![],
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self._value_vec.capacity()
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        self._value_vec.reserve(additional)
     }
 
     pub fn push(&mut self, item: T) {
@@ -22,7 +81,11 @@ impl<T> Stack<T> {
     }
 
     pub fn pop(&mut self) -> Option<T> {
-        self._value_vec.pop()
+        let item = self._value_vec.pop();
+
+        self.clamp_marks();
+
+        item
     }
 
     pub fn peek(&self) -> Option<&T> {
@@ -41,24 +104,133 @@ impl<T> Stack<T> {
         self._value_vec.len() == 0
     }
 
-    /// FILO
-    pub fn stack_iter<'a>(&'a self) -> impl Iterator<Item=&'a T> + 'a {
-        let mut iter = self._value_vec.iter();
+    /// Top to bottom
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            inner: self._value_vec.iter().rev(),
+        }
+    }
+
+    /// Top to bottom
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            inner: self._value_vec.iter_mut().rev(),
+        }
+    }
+
+    /// FILO (top to bottom), same with iter
+    pub fn stack_iter(&self) -> Iter<'_, T> {
+        self.iter()
+    }
+
+    /// FIFO (bottom to top)
+    pub fn queue_iter(&self) -> Rev<Iter<'_, T>> {
+        self.iter().rev()
+    }
+
+    /// Top `k` items from top to bottom (same with iter),
+    /// None if there are less than `k` items
+    pub fn peek_n(&self, k: usize) -> Option<Iter<'_, T>> {
+        let len = self.len();
 
-        std::iter::from_fn(move || {
-            iter.next()
+        if k > len {
+            return None;
+        }
+
+        Some(Iter {
+            inner: self._value_vec[len - k..].iter().rev(),
         })
+    }
+
+    /// Pop top `k` items from top to bottom (same with iter),
+    /// None (and nothing popped) if there are less than `k` items
+    pub fn pop_n(&mut self, k: usize) -> Option<Vec<T>> {
+        let len = self.len();
+
+        if k > len {
+            return None;
+        }
+
+        let mut items = self._value_vec.split_off(len - k);
+
+        items.reverse();
+        self.clamp_marks();
+
+        Some(items)
+    }
+
+    /// Keep the bottom `len` items
+    pub fn truncate(&mut self, len: usize) {
+        self._value_vec.truncate(len);
+        self.clamp_marks();
+    }
+
+    pub fn clear(&mut self) {
+        self.truncate(0)
+    }
+
+    /// Remove all items, return them from top to bottom (same with iter)
+    pub fn drain(&mut self) -> Rev<vec::Drain<'_, T>> {
+        self._marks.iter_mut().for_each(|mark_len| *mark_len = 0);
+        self._value_vec.drain(..).rev()
+    }
 
-        // AnIteratorWrapper {
-        //     iter: box self._value_vec.iter().rev()
-        // }
+    /// Keep the items which `f` returns true, marks are kept on the same
+    /// frame boundaries
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut f: F) {
+        let marks = &mut self._marks;
+        let mut i = 0;
+        let mut kept = 0;
+        let mut mark_i = 0;
+
+        self._value_vec.retain(|item| {
+            while mark_i < marks.len() && marks[mark_i] == i {
+                marks[mark_i] = kept;
+                mark_i += 1;
+            }
+
+            i += 1;
+
+            let keep = f(item);
+
+            if keep {
+                kept += 1;
+            }
+
+            keep
+        });
+
+        marks[mark_i..]
+            .iter_mut()
+            .for_each(|mark_len| *mark_len = kept);
     }
 
-    /// FIFO
-    pub fn queue_iter(&self) -> AnIteratorWrapper<'_, &T> {
-        AnIteratorWrapper {
-            iter: Box::new(self._value_vec.iter()),
+    /// ( a b -- b a ), false (and nothing changed) if there are less than
+    /// 2 items
+    pub fn swap_top(&mut self) -> bool {
+        let len = self.len();
+
+        if len < 2 {
+            return false;
+        }
+
+        self._value_vec.swap(len - 1, len - 2);
+
+        true
+    }
+
+    /// ( a b c -- b c a ), false (and nothing changed) if there are less than
+    /// 3 items
+    pub fn rot(&mut self) -> bool {
+        let len = self.len();
+
+        if len < 3 {
+            return false;
         }
+
+        self._value_vec[len - 3..].rotate_left(1);
+
+        true
     }
 
     /// This method will move the content of stack
@@ -67,6 +239,97 @@ impl<T> Stack<T> {
             self.push(item);
         }
     }
+
+    /// Start a new frame at current top
+    pub fn mark(&mut self) -> StackMark {
+        let idx = self._marks.len();
+        let epoch = NEXT_MARK_EPOCH.fetch_add(1, Relaxed);
+
+        self._marks.push(self.len());
+        self._mark_epochs.push(epoch);
+
+        StackMark { idx, epoch }
+    }
+
+    /// Drop the frames since mark (include it)
+    ///
+    /// # Panics
+    ///
+    /// Panic if the mark has been released
+    pub fn truncate_to(&mut self, mark: StackMark) {
+        let len = self.release(mark);
+
+        self._value_vec.truncate(len);
+    }
+
+    /// Remove the frames since mark (include it),
+    /// return the removed items from top to bottom
+    ///
+    /// # Panics
+    ///
+    /// Panic if the mark has been released
+    pub fn drain_to(&mut self, mark: StackMark) -> Rev<vec::Drain<'_, T>> {
+        let len = self.release(mark);
+
+        self._value_vec.drain(len..).rev()
+    }
+
+    /// Mark and return a guard which rolls back to the mark on drop
+    pub fn scope(&mut self) -> StackGuard<'_, T> {
+        let mark = self.mark();
+
+        StackGuard { stack: self, mark }
+    }
+
+    /// Items between marks from the top frame to the bottom frame,
+    /// the bottom frame is before the first mark
+    pub fn frames(&self) -> impl DoubleEndedIterator<Item = &[T]> {
+        let len = self.len();
+        let marks = &self._marks;
+
+        (0..=marks.len()).rev().map(move |i| {
+            let start = if i == 0 { 0 } else { marks[i - 1] };
+            let end = marks.get(i).cloned().unwrap_or(len);
+
+            &self._value_vec[start..end]
+        })
+    }
+
+    /// Whether the mark hasn't been released
+    pub fn is_marked(&self, mark: StackMark) -> bool {
+        self._mark_epochs.get(mark.idx) == Some(&mark.epoch)
+    }
+
+    /// Remove the mark and the later marks, return the length of the mark
+    fn release(&mut self, mark: StackMark) -> usize {
+        self.try_release(mark)
+            .expect("stack mark has been released")
+    }
+
+    fn try_release(&mut self, mark: StackMark) -> Option<usize> {
+        if !self.is_marked(mark) {
+            return None;
+        }
+
+        let len = self._marks[mark.idx];
+        self._marks.truncate(mark.idx);
+        self._mark_epochs.truncate(mark.idx);
+
+        Some(len)
+    }
+
+    /// Items may be removed below marks
+    fn clamp_marks(&mut self) {
+        let len = self.len();
+
+        for mark_len in self._marks.iter_mut().rev() {
+            if *mark_len <= len {
+                break;
+            }
+
+            *mark_len = len;
+        }
+    }
 }
 
 
@@ -75,6 +338,38 @@ impl<T: Clone> Stack<T> {
     pub fn to_vec(&self) -> Vec<T> {
         self._value_vec.iter().rev().cloned().collect::<Vec<T>>()
     }
+
+    /// ( a -- a a ), false if the stack is empty
+    pub fn dup(&mut self) -> bool {
+        let Some(top) = self.peek().cloned() else {
+            return false;
+        };
+
+        self.push(top);
+
+        true
+    }
+}
+
+/// Compare items only, marks are ignored
+impl<T: PartialEq> PartialEq for Stack<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self._value_vec == other._value_vec
+    }
+}
+
+impl<T: Eq> Eq for Stack<T> {}
+
+impl<T: Hash> Hash for Stack<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self._value_vec.hash(state)
+    }
+}
+
+impl<T> Default for Stack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl<T> iter::IntoIterator for Stack<T> {
@@ -86,15 +381,36 @@ impl<T> iter::IntoIterator for Stack<T> {
     }
 }
 
-/// rev order of Vec
+/// Vec is in top to bottom order, same with to_vec
 impl<T> From<Vec<T>> for Stack<T> {
     fn from(income: Vec<T>) -> Self {
         Self {
             _value_vec: income.into_iter().rev().collect(),
+            _marks: vec![],
+            _mark_epochs: vec![],
         }
     }
 }
 
+impl<'a, T> iter::IntoIterator for &'a Stack<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> iter::IntoIterator for &'a mut Stack<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// push in order (the last one is on top)
 impl<T> Extend<T> for Stack<T> {
     fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
         for item in iter {
@@ -142,79 +458,1082 @@ impl<T: fmt::Display> fmt::Display for Stack<T> {
     }
 }
 
+impl<'a, T> StackGuard<'a, T> {
+    /// Keep the changes, only release the mark
+    /// (nothing to do if it has been released through the guard)
+    pub fn keep(guard: Self) {
+        let mut guard = std::mem::ManuallyDrop::new(guard);
+        let mark = guard.mark;
+
+        guard.stack.try_release(mark);
+    }
 
-pub struct AnIteratorWrapper<'a, T> {
-    pub iter: Box<dyn Iterator<Item=T> + 'a>
+    pub fn mark(guard: &Self) -> StackMark {
+        guard.mark
+    }
 }
 
+impl<'a, T> Deref for StackGuard<'a, T> {
+    type Target = Stack<T>;
 
-impl<'a, T> Iterator for AnIteratorWrapper<'a, T> {
-    type Item = T;
+    fn deref(&self) -> &Self::Target {
+        self.stack
+    }
+}
 
-    fn next(&mut self) -> Option<Self::Item> {
-        self.iter.next()
+impl<'a, T> DerefMut for StackGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.stack
     }
 }
 
+/// Never panic, the mark may have been released through the guard
+/// (`truncate_to` an outer mark), then there is nothing to roll back
+impl<'a, T> Drop for StackGuard<'a, T> {
+    fn drop(&mut self) {
+        if let Some(len) = self.stack.try_release(self.mark) {
+            self.stack._value_vec.truncate(len);
+        }
+    }
+}
 
 
+////////////////////////////////////////////////////////////////////////////////
+/////// BoundedStack
 
-#[macro_export]
-macro_rules! stack {
-    ( $($value:expr),* ) => {
-        {
-            let mut _stack = $crate::Stack::new();
+/// What `BoundedStack::push` does when the stack is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Reject the income item
+    Reject,
+    /// Drop the oldest (bottom) item, like a ring buffer
+    DropOldest,
+    /// Panic when pushing onto a full stack (`try_push` never panics),
+    /// with message "stack overflow (capacity N)"
+    Panic,
+}
 
-            $(
-                _stack.push($value);
-            )*
+/// Capacity-limited stack
+#[derive(Debug, Clone)]
+pub struct BoundedStack<T> {
+    _value_deque: VecDeque<T>,
+    cap: usize,
+    policy: OverflowPolicy,
+    high_water_mark: usize,
+}
 
-            _stack
+impl<T> BoundedStack<T> {
+    pub fn new(cap: usize, policy: OverflowPolicy) -> Self {
+        Self {
+            _value_deque: VecDeque::with_capacity(cap),
+            cap,
+            policy,
+            high_water_mark: 0,
         }
-    };
+    }
+
+    /// Reject on overflow regardless of policy
+    pub fn try_push(&mut self, item: T) -> Result<(), T> {
+        if self.is_full() {
+            return Err(item);
+        }
+
+        self._value_deque.push_back(item);
+        self.high_water_mark = self.high_water_mark.max(self.len());
+
+        Ok(())
+    }
+
+    /// Push according to the overflow policy,
+    /// return the item which isn't kept (the rejected or the dropped one)
+    ///
+    /// # Panics
+    ///
+    /// Panic on overflow with `OverflowPolicy::Panic`
+    pub fn push(&mut self, item: T) -> Option<T> {
+        let item = match self.try_push(item) {
+            Ok(()) => return None,
+            Err(item) => item,
+        };
+
+        match self.policy {
+            OverflowPolicy::Reject => Some(item),
+            OverflowPolicy::DropOldest => {
+                if self.cap == 0 {
+                    return Some(item);
+                }
+
+                let oldest = self._value_deque.pop_front();
+                self._value_deque.push_back(item);
+
+                oldest
+            }
+            OverflowPolicy::Panic => {
+                panic!("stack overflow (capacity {})", self.cap)
+            }
+        }
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        self._value_deque.pop_back()
+    }
+
+    pub fn peek(&self) -> Option<&T> {
+        self._value_deque.back()
+    }
+
+    pub fn peek_mut(&mut self) -> Option<&mut T> {
+        self._value_deque.back_mut()
+    }
+
+    pub fn len(&self) -> usize {
+        self._value_deque.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self._value_deque.is_empty()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len() >= self.cap
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.cap
+    }
+
+    pub fn policy(&self) -> OverflowPolicy {
+        self.policy
+    }
+
+    /// Max length ever reached
+    pub fn high_water_mark(&self) -> usize {
+        self.high_water_mark
+    }
+
+    pub fn reset_high_water_mark(&mut self) {
+        self.high_water_mark = self.len();
+    }
+
+    /// Top to bottom
+    pub fn iter(&self) -> Rev<vec_deque::Iter<'_, T>> {
+        self._value_deque.iter().rev()
+    }
 }
 
 
-#[cfg(test)]
-mod test {
-    use crate::*;
+////////////////////////////////////////////////////////////////////////////////
+/////// MinMaxStack
 
-    #[derive(Debug, Default)]
-    struct Foo {
-        pub(crate) bar: usize,
+/// Stack with O(1) min and max
+#[derive(Debug, Clone)]
+pub struct MinMaxStack<T> {
+    _value_vec: Vec<T>,
+    /// index of (min, max) for each prefix
+    _min_max: Vec<(usize, usize)>,
+}
+
+impl<T: Ord> MinMaxStack<T> {
+    pub fn new() -> Self {
+        Self {
+            _value_vec: vec![],
+            _min_max: vec![],
+        }
     }
 
+    pub fn push(&mut self, item: T) {
+        let idx = self._value_vec.len();
 
-    #[test]
-    fn test_stack_struct() {
-        let mut stack = Stack::new();
-        stack.extend(vec![1, 2, 3].into_iter());
+        let min_max = match self._min_max.last() {
+            Some(&(min, max)) => (
+                if item < self._value_vec[min] { idx } else { min },
+                if item > self._value_vec[max] { idx } else { max },
+            ),
+            None => (idx, idx),
+        };
 
-        assert_eq!(stack.to_vec(), vec![3, 2, 1]);
-        assert_eq!(stack![1, 2, 3].to_vec(), vec![3, 2, 1]);
+        self._value_vec.push(item);
+        self._min_max.push(min_max);
+    }
 
-        stack.extend_stack(stack![4, 5]);
-        assert_eq!(stack.to_vec(), vec![5, 4, 3, 2, 1]);
+    pub fn pop(&mut self) -> Option<T> {
+        self._min_max.pop();
+        self._value_vec.pop()
+    }
 
-        println!("{:?}", stack);
-        println!("{:#?}", stack);
-        println!("{}", stack);
+    pub fn peek(&self) -> Option<&T> {
+        self._value_vec.last()
+    }
 
-        for e in stack.stack_iter() {
-            println!("{}", e);
+    pub fn min(&self) -> Option<&T> {
+        self._min_max.last().map(|&(min, _)| &self._value_vec[min])
+    }
+
+    pub fn max(&self) -> Option<&T> {
+        self._min_max.last().map(|&(_, max)| &self._value_vec[max])
+    }
+
+    pub fn len(&self) -> usize {
+        self._value_vec.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self._value_vec.is_empty()
+    }
+
+    /// Top to bottom
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            inner: self._value_vec.iter().rev(),
         }
     }
+}
 
-    #[test]
-    fn test_stack_peek_mut() {
-        let mut stack0 = stack![Foo::default()];
+impl<T: Ord> Default for MinMaxStack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-        assert_eq!(stack0.peek().unwrap().bar, 0);
 
-        if let Some(foo_mut) = stack0.peek_mut() {
-            foo_mut.bar += 1;
+////////////////////////////////////////////////////////////////////////////////
+/////// MonotonicStack
+
+/// Pop the top items which `should_pop(top, income)` before push
+#[derive(Clone)]
+pub struct MonotonicStack<T, F = fn(&T, &T) -> bool> {
+    _value_vec: Vec<T>,
+    should_pop: F,
+}
+
+impl<T: Ord> MonotonicStack<T> {
+    /// Non-decreasing from bottom to top,
+    /// the popped items meet their next smaller item
+    pub fn increasing() -> Self {
+        Self::new(|top, income| top > income)
+    }
+
+    /// Non-increasing from bottom to top,
+    /// the popped items meet their next greater item
+    pub fn decreasing() -> Self {
+        Self::new(|top, income| top < income)
+    }
+}
+
+impl<T, F: Fn(&T, &T) -> bool> MonotonicStack<T, F> {
+    pub fn new(should_pop: F) -> Self {
+        Self {
+            _value_vec: vec![],
+            should_pop,
         }
+    }
 
-        assert_eq!(stack0.peek().unwrap().bar, 1);
+    /// Return the popped items from top to bottom
+    pub fn push(&mut self, item: T) -> Vec<T> {
+        let mut popped = vec![];
+
+        while let Some(top) = self._value_vec.last() {
+            if !(self.should_pop)(top, &item) {
+                break;
+            }
+
+            popped.push(self._value_vec.pop().unwrap());
+        }
+
+        self._value_vec.push(item);
+
+        popped
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        self._value_vec.pop()
+    }
+
+    pub fn peek(&self) -> Option<&T> {
+        self._value_vec.last()
+    }
+
+    pub fn len(&self) -> usize {
+        self._value_vec.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self._value_vec.is_empty()
+    }
+
+    /// Top to bottom
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            inner: self._value_vec.iter().rev(),
+        }
+    }
+}
+
+impl<T: fmt::Debug, F> fmt::Debug for MonotonicStack<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MonotonicStack")
+            .field("_value_vec", &self._value_vec)
+            .finish()
+    }
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+/////// PStack
+
+/// Persistent stack, versions share tails
+pub struct PStack<T> {
+    head: Option<Rc<PNode<T>>>,
+}
+
+struct PNode<T> {
+    value: T,
+    len: usize,
+    next: Option<Rc<PNode<T>>>,
+}
+
+impl<T> PStack<T> {
+    pub fn new() -> Self {
+        Self { head: None }
+    }
+
+    /// O(1), return the new version
+    pub fn push(&self, item: T) -> Self {
+        Self {
+            head: Some(Rc::new(PNode {
+                value: item,
+                len: self.len() + 1,
+                next: self.head.clone(),
+            })),
+        }
+    }
+
+    /// O(1), return the new version without the top item
+    pub fn pop(&self) -> Option<Self> {
+        self.head.as_ref().map(|node| Self {
+            head: node.next.clone(),
+        })
+    }
+
+    pub fn peek(&self) -> Option<&T> {
+        self.head.as_ref().map(|node| &node.value)
+    }
+
+    pub fn len(&self) -> usize {
+        self.head.as_ref().map(|node| node.len).unwrap_or_default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    /// Top to bottom
+    pub fn iter(&self) -> PIter<'_, T> {
+        PIter {
+            next: self.head.as_deref(),
+        }
+    }
+
+    /// Whether two versions share the same top node
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        match (&this.head, &other.head) {
+            (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+}
+
+impl<T> Clone for PStack<T> {
+    /// O(1)
+    fn clone(&self) -> Self {
+        Self {
+            head: self.head.clone(),
+        }
+    }
+}
+
+impl<T> Default for PStack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for PStack<T> {
+    /// Drop unshared nodes iteratively to avoid recursion on long chain
+    fn drop(&mut self) {
+        let mut head = self.head.take();
+
+        while let Some(node) = head {
+            match Rc::try_unwrap(node) {
+                Ok(mut node) => head = node.next.take(),
+                Err(_) => break,
+            }
+        }
+    }
+}
+
+impl<T> From<Stack<T>> for PStack<T> {
+    fn from(stack: Stack<T>) -> Self {
+        let mut pstack = Self::new();
+
+        for item in stack._value_vec {
+            pstack = pstack.push(item);
+        }
+
+        pstack
+    }
+}
+
+impl<T: Clone> From<&PStack<T>> for Stack<T> {
+    fn from(pstack: &PStack<T>) -> Self {
+        let mut _value_vec = pstack.iter().cloned().collect::<Vec<T>>();
+        _value_vec.reverse();
+
+        Self {
+            _value_vec,
+            _marks: vec![],
+            _mark_epochs: vec![],
+        }
+    }
+}
+
+impl<'a, T> iter::IntoIterator for &'a PStack<T> {
+    type Item = &'a T;
+    type IntoIter = PIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for PStack<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = self
+            .iter()
+            .map(|item| {
+                if f.alternate() {
+                    format!("{:#?}", item)
+                }
+                else {
+                    format!("{:?}", item)
+                }
+            })
+            .collect::<Vec<String>>()
+            .join(" ");
+
+        write!(f, "{}", msg)
+    }
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+/////// Iterator
+
+/// Top to bottom iterator of Stack
+#[derive(Clone)]
+pub struct Iter<'a, T> {
+    inner: Rev<slice::Iter<'a, T>>,
+}
+
+/// Top to bottom mutable iterator of Stack
+pub struct IterMut<'a, T> {
+    inner: Rev<slice::IterMut<'a, T>>,
+}
+
+/// Top to bottom iterator of PStack
+pub struct PIter<'a, T> {
+    next: Option<&'a PNode<T>>,
+}
+
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<'a, T> FusedIterator for Iter<'a, T> {}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+
+impl<'a, T> ExactSizeIterator for IterMut<'a, T> {}
+
+impl<'a, T> FusedIterator for IterMut<'a, T> {}
+
+impl<'a, T> Iterator for PIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.map(|node| {
+            self.next = node.next.as_deref();
+            &node.value
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.next.map(|node| node.len).unwrap_or_default();
+
+        (len, Some(len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for PIter<'a, T> {}
+
+impl<'a, T> FusedIterator for PIter<'a, T> {}
+
+impl<'a, T> Clone for PIter<'a, T> {
+    fn clone(&self) -> Self {
+        Self { next: self.next }
+    }
+}
+
+
+
+
+#[macro_export]
+macro_rules! stack {
+    ( $($value:expr),* ) => {
+        {
+            let mut _stack = $crate::Stack::new();
+
+            $(
+                _stack.push($value);
+            )*
+
+            _stack
+        }
+    };
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[derive(Debug, Default)]
+    struct Foo {
+        pub(crate) bar: usize,
+    }
+
+
+    #[test]
+    fn test_stack_struct() {
+        let mut stack = Stack::new();
+        stack.extend(vec![1, 2, 3].into_iter());
+
+        assert_eq!(stack.to_vec(), vec![3, 2, 1]);
+        assert_eq!(stack![1, 2, 3].to_vec(), vec![3, 2, 1]);
+
+        stack.extend_stack(stack![4, 5]);
+        assert_eq!(stack.to_vec(), vec![5, 4, 3, 2, 1]);
+
+        println!("{:?}", stack);
+        println!("{:#?}", stack);
+        println!("{}", stack);
+
+        for e in stack.stack_iter() {
+            println!("{}", e);
+        }
+    }
+
+    #[test]
+    fn test_stack_peek_mut() {
+        let mut stack0 = stack![Foo::default()];
+
+        assert_eq!(stack0.peek().unwrap().bar, 0);
+
+        if let Some(foo_mut) = stack0.peek_mut() {
+            foo_mut.bar += 1;
+        }
+
+        assert_eq!(stack0.peek().unwrap().bar, 1);
+    }
+
+    #[test]
+    fn test_stack_vec_api() {
+        use std::collections::HashSet;
+
+        let mut stack = Stack::with_capacity(8);
+
+        assert!(stack.capacity() >= 8);
+        stack.reserve(16);
+        assert!(stack.capacity() >= 16);
+
+        stack.extend(1..=6);
+
+        // top to bottom, same with iter and drain
+        assert_eq!(stack.peek_n(0).unwrap().count(), 0);
+        assert_eq!(stack.peek_n(2).unwrap().collect::<Vec<_>>(), [&6, &5]);
+        assert!(stack.peek_n(7).is_none());
+
+        assert_eq!(stack.pop_n(7), None);
+        assert_eq!(stack.pop_n(2), Some(vec![6, 5]));
+        assert_eq!(stack.to_vec(), vec![4, 3, 2, 1]);
+
+        stack.truncate(3);
+        assert_eq!(stack.to_vec(), vec![3, 2, 1]);
+
+        assert!(stack.swap_top());
+        assert_eq!(stack.to_vec(), vec![2, 3, 1]);
+        assert!(stack.rot());
+        assert_eq!(stack.to_vec(), vec![1, 2, 3]);
+        assert!(stack.dup());
+        assert_eq!(stack.to_vec(), vec![1, 1, 2, 3]);
+
+        stack.retain(|&x| x != 1);
+        assert_eq!(stack.to_vec(), vec![2, 3]);
+
+        assert_eq!(stack, Stack::from(vec![2, 3]));
+        assert_ne!(stack, Stack::from(vec![3, 2]));
+        assert_eq!(Stack::<usize>::default(), Stack::new());
+
+        let set = vec![stack.clone(), Stack::from(vec![2, 3]), stack![1]]
+            .into_iter()
+            .collect::<HashSet<_>>();
+
+        assert_eq!(set.len(), 2);
+
+        assert_eq!(stack.drain().collect::<Vec<_>>(), vec![2, 3]);
+        assert!(stack.is_empty());
+        assert!(!stack.swap_top());
+        assert!(!stack.rot());
+        assert!(!stack.dup());
+
+        stack.push(1);
+        stack.clear();
+        assert!(stack.is_empty());
+    }
+
+    #[test]
+    fn test_stack_vec_api_with_marks() {
+        let mut stack = stack![1, 2];
+        let m1 = stack.mark();
+        stack.extend(vec![3, 4]);
+        let m2 = stack.mark();
+        stack.extend(vec![5, 6]);
+
+        stack.retain(|&x| x % 2 == 0);
+        assert_eq!(
+            stack.frames().collect::<Vec<_>>(),
+            vec![&[6][..], &[4], &[2]]
+        );
+
+        stack.truncate(1);
+        assert_eq!(
+            stack.frames().collect::<Vec<_>>(),
+            vec![&[][..], &[], &[2]]
+        );
+
+        stack.extend(vec![7, 8]);
+        assert_eq!(stack.pop_n(2), Some(vec![8, 7]));
+        stack.push(9);
+        assert_eq!(stack.drain_to(m2).collect::<Vec<_>>(), vec![9]);
+
+        stack.push(10);
+        stack.clear();
+        stack.push(11);
+        stack.truncate_to(m1);
+        assert!(stack.is_empty());
+    }
+
+    #[test]
+    fn test_stack_iter_order() {
+        let mut stack = stack![1, 2, 3];
+
+        assert_eq!(stack.peek(), Some(&3));
+
+        assert_eq!(stack.iter().collect::<Vec<_>>(), vec![&3, &2, &1]);
+        assert_eq!(stack.iter().rev().collect::<Vec<_>>(), vec![&1, &2, &3]);
+        assert_eq!(stack.stack_iter().collect::<Vec<_>>(), vec![&3, &2, &1]);
+        assert_eq!(stack.queue_iter().collect::<Vec<_>>(), vec![&1, &2, &3]);
+        assert_eq!((&stack).into_iter().collect::<Vec<_>>(), vec![&3, &2, &1]);
+        assert_eq!(stack.to_vec(), vec![3, 2, 1]);
+        assert_eq!(format!("{}", stack), "3 2 1");
+        assert_eq!(format!("{:?}", stack), "3 2 1");
+
+        let mut iter = stack.iter();
+
+        assert_eq!(iter.len(), 3);
+        assert_eq!(iter.next(), Some(&3));
+        assert_eq!(iter.next_back(), Some(&1));
+        assert_eq!(iter.len(), 1);
+        assert_eq!(iter.next(), Some(&2));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next_back(), None);
+
+        for (i, e) in stack.iter_mut().enumerate() {
+            *e += i * 10;
+        }
+
+        assert_eq!(stack.to_vec(), vec![3, 12, 21]);
+
+        for e in &mut stack {
+            *e += 1;
+        }
+
+        assert_eq!(stack.to_vec(), vec![4, 13, 22]);
+        assert_eq!(stack.iter_mut().next_back(), Some(&mut 22));
+        assert_eq!(stack.iter_mut().len(), 3);
+
+        assert_eq!(stack.into_iter().collect::<Vec<_>>(), vec![4, 13, 22]);
+    }
+
+    #[test]
+    fn test_stack_conversion_order() {
+        // Vec is top to bottom
+        let stack = Stack::from(vec![1, 2, 3]);
+
+        assert_eq!(stack.peek(), Some(&1));
+        assert_eq!(stack.iter().collect::<Vec<_>>(), vec![&1, &2, &3]);
+        assert_eq!(stack.to_vec(), vec![1, 2, 3]);
+        assert_eq!(stack.into_iter().collect::<Vec<_>>(), vec![1, 2, 3]);
+
+        // extend pushes in order
+        let mut stack = stack![1];
+        stack.extend(vec![2, 3]);
+
+        assert_eq!(stack.peek(), Some(&3));
+        assert_eq!(stack.to_vec(), vec![3, 2, 1]);
+
+        // extend_stack keeps the order of the income stack on top
+        stack.extend_stack(stack![4, 5]);
+
+        assert_eq!(stack.to_vec(), vec![5, 4, 3, 2, 1]);
+        assert_eq!(stack.pop(), Some(5));
+    }
+
+    #[test]
+    fn test_bounded_stack() {
+        let mut stack = BoundedStack::new(3, OverflowPolicy::Reject);
+
+        assert_eq!(stack.push(1), None);
+        assert_eq!(stack.try_push(2), Ok(()));
+        assert_eq!(stack.push(3), None);
+        assert!(stack.is_full());
+        assert_eq!(stack.push(4), Some(4));
+        assert_eq!(stack.try_push(5), Err(5));
+        assert_eq!(stack.iter().collect::<Vec<_>>(), vec![&3, &2, &1]);
+
+        assert_eq!(stack.pop(), Some(3));
+        assert_eq!(stack.pop(), Some(2));
+        assert_eq!(stack.high_water_mark(), 3);
+
+        stack.reset_high_water_mark();
+        assert_eq!(stack.high_water_mark(), 1);
+        stack.push(6);
+        assert_eq!(stack.high_water_mark(), 2);
+
+        let mut stack = BoundedStack::new(3, OverflowPolicy::DropOldest);
+
+        for i in 1..=3 {
+            assert_eq!(stack.push(i), None);
+        }
+
+        assert_eq!(stack.push(4), Some(1));
+        assert_eq!(stack.push(5), Some(2));
+        assert_eq!(stack.try_push(6), Err(6));
+        assert_eq!(stack.peek(), Some(&5));
+        assert_eq!(stack.iter().collect::<Vec<_>>(), vec![&5, &4, &3]);
+        assert_eq!(stack.len(), 3);
+        assert_eq!(stack.high_water_mark(), 3);
+
+        let mut stack = BoundedStack::new(0, OverflowPolicy::DropOldest);
+
+        assert_eq!(stack.push(1), Some(1));
+        assert!(stack.is_empty());
+    }
+
+    #[test]
+    fn test_stack_frame() {
+        let mut stack = stack![1, 2];
+
+        let m1 = stack.mark();
+        stack.push(3);
+        stack.push(4);
+
+        let m2 = stack.mark();
+        stack.push(5);
+
+        assert_eq!(
+            stack.frames().collect::<Vec<_>>(),
+            vec![&[5][..], &[3, 4], &[1, 2]]
+        );
+        assert_eq!(stack.frames().next_back(), Some(&[1, 2][..]));
+
+        assert_eq!(stack.drain_to(m2).collect::<Vec<_>>(), vec![5]);
+        assert_eq!(stack.frames().count(), 2);
+
+        let m2 = stack.mark();
+        assert_eq!(stack.frames().next(), Some(&[][..]));
+        stack.push(6);
+
+        stack.truncate_to(m1);
+        assert_eq!(stack.to_vec(), vec![2, 1]);
+        assert_eq!(stack.frames().collect::<Vec<_>>(), vec![&[1, 2][..]]);
+
+        let result = std::panic::catch_unwind(
+            std::panic::AssertUnwindSafe(|| stack.truncate_to(m2)),
+        );
+
+        assert!(result.is_err());
+
+        // pop below the mark
+        let m1 = stack.mark();
+        stack.pop();
+        let m2 = stack.mark();
+        stack.push(7);
+
+        assert_eq!(
+            stack.frames().collect::<Vec<_>>(),
+            vec![&[7][..], &[], &[1]]
+        );
+
+        stack.truncate_to(m2);
+        assert_eq!(stack.to_vec(), vec![1]);
+        stack.push(8);
+        stack.truncate_to(m1);
+        assert_eq!(stack.to_vec(), vec![1]);
+    }
+
+    #[test]
+    fn test_stack_guard() {
+        let mut stack = stack![1];
+
+        {
+            let mut guard = stack.scope();
+            guard.push(2);
+
+            {
+                let mut inner = guard.scope();
+                inner.push(3);
+
+                assert_eq!(inner.frames().count(), 3);
+                assert_eq!(inner.to_vec(), vec![3, 2, 1]);
+            }
+
+            assert_eq!(guard.to_vec(), vec![2, 1]);
+
+            let mut inner = guard.scope();
+            inner.push(4);
+            StackGuard::keep(inner);
+
+            assert_eq!(guard.to_vec(), vec![4, 2, 1]);
+            assert_eq!(guard.frames().count(), 2);
+        }
+
+        assert_eq!(stack.to_vec(), vec![1]);
+        assert_eq!(stack.frames().count(), 1);
+
+        // the guard's mark is released through the guard
+        let outer = stack.mark();
+        {
+            let mut guard = stack.scope();
+            guard.push(2);
+            guard.truncate_to(outer);
+            guard.push(3);
+        }
+
+        assert_eq!(stack.to_vec(), vec![3, 1]);
+        assert_eq!(stack.frames().count(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "stack mark has been released")]
+    fn test_stack_stale_mark() {
+        let mut stack = stack![1];
+
+        let m_old = stack.mark();
+        stack.push(2);
+        stack.truncate_to(m_old);
+
+        let m_new = stack.mark();
+        stack.push(3);
+
+        assert!(!stack.is_marked(m_old));
+        assert!(stack.is_marked(m_new));
+
+        stack.truncate_to(m_old);
+    }
+
+    #[test]
+    fn test_stack_foreign_mark() {
+        let mut stack_a = stack![1];
+        let mut stack_b = stack![1];
+
+        let mark_a = stack_a.mark();
+        let mark_b = stack_b.mark();
+        stack_a.push(2);
+        stack_b.push(2);
+
+        assert_ne!(mark_a, mark_b);
+        assert!(stack_a.is_marked(mark_a));
+        assert!(!stack_b.is_marked(mark_a));
+
+        // a guard over the foreign mark releases nothing
+        {
+            let _guard = StackGuard { stack: &mut stack_b, mark: mark_a };
+        }
+
+        assert!(stack_b.is_marked(mark_b));
+        assert_eq!(stack_b.to_vec(), [2, 1]);
+        assert_eq!(stack_a.drain_to(mark_a).collect::<Vec<_>>(), [2]);
+    }
+
+    #[test]
+    fn test_min_max_stack() {
+        let mut stack = MinMaxStack::new();
+
+        assert_eq!(stack.min(), None);
+        assert_eq!(stack.max(), None);
+
+        for (item, min, max) in
+            [(5, 5, 5), (3, 3, 5), (7, 3, 7), (3, 3, 7), (1, 1, 7), (9, 1, 9)]
+        {
+            stack.push(item);
+            assert_eq!((stack.min(), stack.max()), (Some(&min), Some(&max)));
+        }
+
+        assert_eq!(stack.iter().collect::<Vec<_>>(), [&9, &1, &3, &7, &3, &5]);
+
+        for (min, max) in [(1, 7), (3, 7), (3, 7), (3, 5), (5, 5)] {
+            stack.pop();
+            assert_eq!((stack.min(), stack.max()), (Some(&min), Some(&max)));
+        }
+
+        assert_eq!(stack.pop(), Some(5));
+        assert_eq!(stack.min(), None);
+        assert!(stack.is_empty());
+    }
+
+    #[test]
+    fn test_monotonic_stack() {
+        // next greater element
+        let nums = [2, 1, 2, 4, 3, 1];
+        let mut next_greater = vec![None; nums.len()];
+        let mut stack =
+            MonotonicStack::new(|&(_, top): &(usize, i32), &(_, income)| {
+                top < income
+            });
+
+        for (i, &x) in nums.iter().enumerate() {
+            for (j, _) in stack.push((i, x)) {
+                next_greater[j] = Some(x);
+            }
+        }
+
+        assert_eq!(
+            next_greater,
+            [Some(4), Some(2), Some(4), None, None, None]
+        );
+
+        let mut stack = MonotonicStack::increasing();
+
+        assert_eq!(stack.push(3), vec![]);
+        assert_eq!(stack.push(5), vec![]);
+        assert_eq!(stack.push(5), vec![]);
+        assert_eq!(stack.push(4), vec![5, 5]);
+        assert_eq!(stack.push(1), vec![4, 3]);
+        assert_eq!(stack.iter().collect::<Vec<_>>(), [&1]);
+
+        let mut stack = MonotonicStack::decreasing();
+
+        stack.push(3);
+        stack.push(1);
+        stack.push(1);
+
+        assert_eq!(stack.push(2), vec![1, 1]);
+        assert_eq!(stack.peek(), Some(&2));
+        assert_eq!(stack.len(), 2);
+        assert_eq!(stack.pop(), Some(2));
+    }
+
+    #[test]
+    fn test_pstack() {
+        let s0 = PStack::new();
+        let s1 = s0.push(1);
+        let s2 = s1.push(2);
+        let s3a = s2.push(3);
+        let s3b = s2.push(30);
+
+        assert!(s0.is_empty());
+        assert_eq!(s0.pop().map(|s| s.len()), None);
+        assert_eq!(s2.len(), 2);
+        assert_eq!(s3a.peek(), Some(&3));
+        assert_eq!(s3b.peek(), Some(&30));
+        assert_eq!(s3a.iter().collect::<Vec<_>>(), vec![&3, &2, &1]);
+        assert_eq!(s3b.iter().collect::<Vec<_>>(), vec![&30, &2, &1]);
+        assert_eq!(s3b.iter().len(), 3);
+
+        let s2b = s3b.pop().unwrap();
+
+        assert!(PStack::ptr_eq(&s2, &s2b));
+        assert!(!PStack::ptr_eq(&s3a, &s3b));
+        assert_eq!(s2b.pop().unwrap().peek(), Some(&1));
+        assert_eq!(format!("{:?}", s3a), "3 2 1");
+
+        let stack = Stack::from(&s3a);
+
+        assert_eq!(stack.peek(), Some(&3));
+        assert_eq!(stack.to_vec(), vec![3, 2, 1]);
+
+        let pstack = PStack::from(stack);
+
+        let items = (&pstack).into_iter().collect::<Vec<_>>();
+
+        assert_eq!(items, vec![&3, &2, &1]);
+        assert_eq!(pstack.len(), 3);
+
+        // long chain shouldn't overflow the call stack on drop
+        let mut long = PStack::new();
+
+        for i in 0..1_000_000 {
+            long = long.push(i);
+        }
+
+        let shared = long.pop().unwrap();
+
+        drop(long);
+        assert_eq!(shared.peek(), Some(&999_998));
+    }
+
+    #[test]
+    #[should_panic(expected = "stack overflow")]
+    fn test_bounded_stack_panic() {
+        let mut stack = BoundedStack::new(2, OverflowPolicy::Panic);
+
+        stack.push(1);
+        stack.push(2);
+        stack.push(3);
     }
 }
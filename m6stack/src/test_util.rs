@@ -0,0 +1,30 @@
+//! Test fixtures shared by the modules
+
+use std::sync::atomic::{AtomicUsize, Ordering::SeqCst};
+use std::sync::Arc;
+
+/// Count the drops of the values it makes, shared between threads
+#[derive(Clone, Default)]
+pub(crate) struct DropCounter(Arc<AtomicUsize>);
+
+pub(crate) struct Counted(Arc<AtomicUsize>);
+
+impl DropCounter {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn make(&self) -> Counted {
+        Counted(self.0.clone())
+    }
+
+    pub(crate) fn get(&self) -> usize {
+        self.0.load(SeqCst)
+    }
+}
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        self.0.fetch_add(1, SeqCst);
+    }
+}